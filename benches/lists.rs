@@ -0,0 +1,39 @@
+#[macro_use]
+extern crate criterion;
+extern crate alisplib;
+
+use criterion::BenchmarkId;
+use criterion::Criterion;
+
+use alisplib::interpreter::Interpreter;
+
+//typical list code in loops, the time per element should stay the same when the lists grow
+const SIZES: [usize; 3] = [250, 1000, 4000];
+
+//builds a list with cons, the list is still referenced by the variable while cons is evaluated
+const CONS_LOOP: &str = "seq (set 'xs '()) (set 'i 0) (while '(lt i N) '(seq (set 'xs (cons i xs)) (set 'i (+ i 1))))";
+
+//takes the tail until the list is empty
+const TAIL_WALK: &str = "seq (set 'xs (count 1 N)) (while '(lt 0 (len xs)) '(set 'xs (tail xs)))";
+
+//reads every element by its index, which resolves the list for every access
+const NTH_LOOP: &str = "seq (set 'xs (count 1 N)) (set 'i 0) (set 's 0) (while '(lt i N) '(seq (set 's (+ s (nth i xs))) (set 'i (+ i 1))))";
+
+fn run(code: &str, size: usize) {
+    let mut interpreter = Interpreter::new_empty();
+    interpreter.eval_string(code.replace("N", &size.to_string())).unwrap();
+}
+
+fn bench_lists(c: &mut Criterion) {
+    for &(name, code) in &[("cons-loop", CONS_LOOP), ("tail-walk", TAIL_WALK), ("nth-loop", NTH_LOOP)] {
+        let mut group = c.benchmark_group(name);
+        group.sample_size(10);
+        for size in SIZES.iter() {
+            group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, &size| b.iter(|| run(code, size)));
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_lists);
+criterion_main!(benches);
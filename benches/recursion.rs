@@ -0,0 +1,44 @@
+#[macro_use]
+extern crate criterion;
+extern crate alisplib;
+
+use criterion::BenchmarkId;
+use criterion::Criterion;
+
+use alisplib::interpreter::Interpreter;
+
+//recursive functions that spend most of their time resolving parameters and calling lambdas
+const FIB: &str = "seq (set 'fib (|n| match (lt n 2) (true n) (false (+ (fib (- n 1)) (fib (- n 2)))))) (fib N)";
+
+const TAK: &str = "seq (set 'tak (|x y z| match (lt y x) (false z) (true (tak (tak (- x 1) y z) (tak (- y 1) z x) (tak (- z 1) x y))))) (tak N (/ N 2) 0)";
+
+//every call binds a local with let, so the references go through two frames
+const SUM_LET: &str = "seq (set 'sum-to (|n acc| match (eq n 0) (true acc) (false (let ((m (- n 1)) (s (+ acc n))) (sum-to m s))))) (sum-to N 0)";
+
+//the usual style with if and quoted branches, which are evaluated by name inside of if
+const FIB_IF: &str = "seq (set 'fib (|n| if (lt n 2) 'n '(+ (fib (- n 1)) (fib (- n 2))))) (fib N)";
+
+fn run(interpreter: &mut Interpreter, code: &str, size: usize) {
+    interpreter.eval_string(code.replace("N", &size.to_string())).unwrap();
+}
+
+fn bench_recursion(c: &mut Criterion) {
+    let benches: [(&str, &str, [usize; 2]); 4] = [
+        ("fib", FIB, [12, 16]),
+        ("tak", TAK, [8, 10]),
+        ("sum-let", SUM_LET, [100, 400]),
+        ("fib-if", FIB_IF, [12, 16])
+    ];
+    let mut interpreter = Interpreter::new();
+    for &(name, code, sizes) in benches.iter() {
+        let mut group = c.benchmark_group(name);
+        group.sample_size(10);
+        for size in sizes.iter() {
+            group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, &size| b.iter(|| run(&mut interpreter, code, size)));
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_recursion);
+criterion_main!(benches);
\ No newline at end of file
@@ -0,0 +1,187 @@
+use std::fs;
+use std::io::Error as IOError;
+
+use alisplib::interpreter::Interpreter;
+use alisplib::list::List;
+use alisplib::value::Value;
+
+use parse_units;
+use read_file;
+use split_code;
+use Example;
+
+//runs the examples of the given files and returns the exit code, which is 1 if an example failed
+//.ali files are loaded before their examples run, .csv files are read like corelib.csv
+//without files the examples of the std and corelib.csv in the current directory are run
+pub fn run(files: Vec<String>) -> i32 {
+    let files = match files.len() {
+        0 => match default_files() {
+            Ok(files) => files,
+            Err(err) => {
+                println!("error while looking for the std: {}", err);
+                println!("usage: doctool test [files...], without files it has to run in the directory of corelib.csv");
+                return 2;
+            }
+        },
+        _ => files
+    };
+    let mut passed = 0;
+    let mut failed = 0;
+    for filepath in files {
+        let content = match read_file(filepath.clone()) {
+            Ok(v) => v,
+            Err(err) => {
+                println!("error while reading file '{}': {}", filepath, err);
+                return 2;
+            }
+        };
+        let examples = match filepath.ends_with(".csv") {
+            true => csv_examples(content),
+            false => parse_units(content).map(|units| {
+                let mut examples = Vec::new();
+                for unit in units {
+                    for example in unit.examples {
+                        examples.push((unit.fn_name.clone(), example));
+                    }
+                }
+                examples
+            })
+        };
+        let examples = match examples {
+            Ok(v) => v,
+            Err(err) => {
+                println!("error in file '{}': {}", filepath, err);
+                return 2;
+            }
+        };
+        let mut interpreter = Interpreter::new();
+        if filepath.ends_with(".ali") {
+            match interpreter.load_script(filepath.clone()) {
+                Ok(_) => {},
+                Err(err) => {
+                    println!("error while loading file '{}': {}", filepath, err);
+                    return 2;
+                }
+            }
+        }
+        println!("{}", filepath);
+        for (fn_name, example) in examples {
+            match check(&mut interpreter, &example) {
+                None => {
+                    passed += 1;
+                    println!("    ok    {}: {} = {}", fn_name, example.0, example.1);
+                },
+                Some(failure) => {
+                    failed += 1;
+                    println!("    FAIL  {}: {} = {}", fn_name, example.0, example.1);
+                    for line in failure.lines() {
+                        println!("          {}", line);
+                    }
+                }
+            }
+        }
+    }
+    println!();
+    println!("{} passed, {} failed", passed, failed);
+    match failed {
+        0 => 0,
+        _ => 1
+    }
+}
+
+//the .ali files in std and corelib.csv
+#[allow(clippy::useless_format)]
+fn default_files() -> Result<Vec<String>, IOError> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir("std")? {
+        let path = entry?.path();
+        if path.extension().map(|x| x == "ali").unwrap_or(false) {
+            files.push(path.to_string_lossy().into_owned());
+        }
+    }
+    files.sort();
+    fs::metadata("corelib.csv")?;
+    files.push(format!("corelib.csv"));
+    Ok(files)
+}
+
+//returns a description of the failure, if the example does not hold
+fn check(interpreter: &mut Interpreter, example: &Example) -> Option<String> {
+    let expected = match eval(interpreter, &example.1) {
+        Ok(v) => v,
+        Err(err) => return Some(format!("could not evaluate the expected result: {}", err))
+    };
+    match eval(interpreter, &example.0) {
+        Ok(ref actual) if *actual == expected => None,
+        Ok(actual) => Some(format!("expected: {:?}\n  actual: {:?}", expected, actual)),
+        Err(err) => Some(format!("expected: {:?}\n   error: {}", expected, err))
+    }
+}
+
+//evaluates a single expression the way it would be evaluated as a function argument
+fn eval(interpreter: &mut Interpreter, code: &str) -> Result<Value, String> {
+    let mut cells = match List::from_string(code.to_owned()) {
+        Ok(list) => list.into_cells(),
+        Err(err) => return Err(format!("{}", err).trim_end().to_owned())
+    };
+    if cells.len() != 1 {
+        return Err(format!("expected a single expression, found {}.", cells.len()));
+    }
+    interpreter.eval_value(cells.pop().unwrap()).map_err(|err| format!("{}", err).trim_end().to_owned())
+}
+
+//the examples of the 'Example' column, seperated by ';' like in doc blocks
+//rows without an example or without a stated result are skipped
+#[allow(clippy::useless_format)]
+fn csv_examples(content: String) -> Result<Vec<(String, Example)>, String> {
+    let mut lines = content.lines();
+    let header = match lines.next() {
+        Some(v) => csv_fields(v),
+        None => return Ok(Vec::new())
+    };
+    let column = match header.iter().position(|x| x == "Example") {
+        Some(i) => i,
+        None => return Err(format!("the csv file has no 'Example' column."))
+    };
+    let mut result = Vec::new();
+    for line in lines {
+        let fields = csv_fields(line);
+        let example = match fields.get(column) {
+            Some(v) => v,
+            None => continue
+        };
+        for split in split_code(example, ";") {
+            let parts = split_code(&split, " = ");
+            if parts.len() < 2 {
+                continue;
+            }
+            let fn_name = fields.first().unwrap().to_owned();
+            result.push((fn_name, (parts.first().unwrap().trim().to_owned(), parts.last().unwrap().trim().to_owned())));
+        }
+    }
+    Ok(result)
+}
+
+//splits a csv line at the commas that are not inside of a quoted field
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut buffer = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                buffer.push('"');
+            },
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                fields.push(buffer);
+                buffer = String::new();
+            },
+            ch => buffer.push(ch)
+        }
+    }
+    fields.push(buffer);
+    fields
+}
\ No newline at end of file
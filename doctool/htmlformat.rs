@@ -1,6 +1,7 @@
 use DocUnit;
 use ValDesc;
 
+#[allow(clippy::len_zero, clippy::single_match)]
 pub fn print_units(units: Vec<DocUnit>) {
     let mut style = String::new();
     style.push_str("td {");
@@ -34,6 +35,14 @@ pub fn print_units(units: Vec<DocUnit>) {
             },
             None => {}
         }
+        if elem.examples.len() > 0 {
+            println!("<h3>Examples</h3>");
+            println!("<pre>");
+            for example in elem.examples {
+                println!("{} = {}", example.0, example.1);
+            }
+            println!("</pre>");
+        }
     }
     println!("</body>");
 }
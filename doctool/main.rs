@@ -1,19 +1,29 @@
+extern crate alisplib;
+
 use std::env;
 use std::fs::File;
 use std::io::Read;
 use std::io::Error as IOError;
+use std::process;
 
 mod plainformat;
 mod htmlformat;
+mod doctest;
 
+#[allow(clippy::len_zero)]
 fn main() {
-    let args = env::args().skip(1).collect::<Vec<String>>();
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
     let mut units = Vec::new();
     if args.len() == 0 {
         println!("no arguments were given.");
-        println!("usage: doctool [files...]");
+        println!("usage: doctool [--html] [files...]");
+        println!("       doctool test [files...], the std and corelib.csv without files");
         return;
     }
+    if args.first().unwrap() == "test" {
+        args.remove(0);
+        process::exit(doctest::run(args));
+    }
     let mut htmlformatter = false;
     for filepath in args {
         if filepath == "--html" {
@@ -49,12 +59,28 @@ fn read_file(path: String) -> Result<String, IOError> {
     Ok(buffer)
 }
 
+#[allow(clippy::single_match)]
 fn parse_units(string: String) -> Result<Vec<DocUnit>, String> {
     let mut result = Vec::new();
     let mut buffer = String::new();
     let mut level = 0;
     let mut quoted = false;
+    //inside a doc block only a bracket at the start of a line begins the documented code
+    let mut doc_block = false;
+    let mut line_start = true;
     for ch in string.chars() {
+        let at_line_start = line_start;
+        line_start = ch == '\n';
+        if level == 0 && !quoted {
+            if at_line_start && ch == '@' {
+                doc_block = true;
+            }
+            if doc_block && !(at_line_start && ch == '(') {
+                buffer.push(ch);
+                continue;
+            }
+            doc_block = false;
+        }
         match ch {
             '\"' => {
                 quoted = !quoted;
@@ -89,6 +115,9 @@ fn parse_units(string: String) -> Result<Vec<DocUnit>, String> {
 // a type that contains an element and a description of that element
 type ValDesc = (String, String);
 
+//an expression and the value it is expected to evaluate to
+pub type Example = (String, String);
+
 // a documented function
 #[derive(Debug)]
 pub struct DocUnit {
@@ -96,10 +125,12 @@ pub struct DocUnit {
     pub arguments: Vec<ValDesc>,
     pub throws: Option<Vec<ValDesc>>,
     pub returns: Vec<ValDesc>,
-    pub description: Option<String>
+    pub description: Option<String>,
+    pub examples: Vec<Example>
 }
 
 impl DocUnit {
+    #[allow(clippy::manual_ok_err, clippy::redundant_field_names)]
     pub fn from_string(string: String) -> Result<Option<DocUnit>, String> {
         let fn_name = match DocUnit::parse_doc_property(&string, "function") {
             Ok(v) => v,
@@ -115,23 +146,29 @@ impl DocUnit {
             Ok(v) => Some(DocUnit::parse_valdesc_table(v)?),
             Err(_) => None
         };
+        let examples = match DocUnit::parse_doc_property(&string, "example") {
+            Ok(v) => DocUnit::parse_examples(v)?,
+            Err(_) => Vec::new()
+        };
 
         Ok(Some(DocUnit {
             fn_name: fn_name,
             returns: returns,
             description: description,
             arguments: arguments,
-            throws: throws
+            throws: throws,
+            examples: examples
         }))
     }
 
+    #[allow(clippy::bool_comparison, clippy::needless_return, clippy::ptr_arg)]
     fn parse_doc_property(string: &String, prop_name: &'static str) -> Result<String, String> {
-        let index = match string.find(&prop_name) {
+        let index = match string.find(&format!("@{}", prop_name)) {
             Some(i) => i,
             None => return Err(format!("could not find property '{}'.", prop_name))
         };
         let mut buffer = String::new();
-        let char_iter = string.chars().skip(index);
+        let char_iter = string[index + 1..].chars();
         let mut active = false;
         for ch in char_iter {
             if ch == '@' {
@@ -156,6 +193,7 @@ impl DocUnit {
         Ok(result)
     }
 
+    #[allow(clippy::get_first)]
     fn parse_valdesc(string: String) -> Result<ValDesc, String> {
         let split = string.clone().split(":").map(|x| x.to_owned()).collect::<Vec<String>>();
         if split.len() != 2 && split.len() != 1 {
@@ -170,6 +208,51 @@ impl DocUnit {
         Ok((beautify(val), beautify(desc)))
     }
 
+    //examples are seperated by ';' and have the form 'expression = result'
+    //further '=' parts in between are explanations, only the first and the last part are evaluated
+    #[allow(clippy::len_zero)]
+    fn parse_examples(string: String) -> Result<Vec<Example>, String> {
+        let mut result = Vec::new();
+        for split in split_code(&string, ";") {
+            if beautify(split.clone()).len() == 0 {
+                continue;
+            }
+            let parts = split_code(&split, " = ");
+            if parts.len() < 2 {
+                return Err(format!("expected an example of the form 'expression = result', found:\n{}", beautify(split)));
+            }
+            result.push((beautify(parts.first().unwrap().to_owned()), beautify(parts.last().unwrap().to_owned())));
+        }
+        Ok(result)
+    }
+
+}
+
+//splits the string at the seperator, but not inside of brackets or string literals
+pub fn split_code(string: &str, seperator: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut buffer = String::new();
+    let mut level = 0;
+    let mut quoted = false;
+    let mut rest = string;
+    while let Some(ch) = rest.chars().next() {
+        if level == 0 && !quoted && rest.starts_with(seperator) {
+            result.push(buffer);
+            buffer = String::new();
+            rest = &rest[seperator.len()..];
+            continue;
+        }
+        match ch {
+            '\"' => quoted = !quoted,
+            '(' if !quoted => level += 1,
+            ')' if !quoted => level -= 1,
+            _ => {}
+        }
+        buffer.push(ch);
+        rest = &rest[ch.len_utf8()..];
+    }
+    result.push(buffer);
+    result
 }
 
 fn beautify(string: String) -> String {
@@ -1,6 +1,7 @@
 use DocUnit;
 use ValDesc;
 
+#[allow(clippy::len_zero, clippy::single_match, clippy::useless_format)]
 pub fn print_units(units: Vec<DocUnit>) {
     print_header(format!("Summary"));
     for elem in &units {
@@ -33,6 +34,14 @@ pub fn print_units(units: Vec<DocUnit>) {
             },
             None => {}
         }
+        if elem.examples.len() > 0 {
+            println!("=> Examples");
+            println!();
+            for example in elem.examples {
+                println!(" - {} = {}", example.0, example.1);
+            }
+            println!();
+        }
     }
 }
 
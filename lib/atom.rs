@@ -0,0 +1,130 @@
+use std::cmp::Ordering;
+use std::sync::{
+    Arc,
+    Mutex,
+    MutexGuard,
+    TryLockError,
+    Weak
+};
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering as AtomicOrdering;
+
+use error::Error;
+use gc;
+use value::Value;
+
+//the shared part of an atom, the garbage collector counts the cells that are alive
+#[derive(Debug)]
+pub struct AtomCell {
+    value: Mutex<Value>,
+    //true once the garbage collector knows the atom, see gc
+    tracked: AtomicBool
+}
+
+impl AtomCell {
+    //a panic in another thread does not make the value unusable
+    pub fn lock(&self) -> MutexGuard<'_, Value> {
+        self.value.lock().unwrap_or_else(|x| x.into_inner())
+    }
+
+    //none if another thread holds the lock
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, Value>> {
+        match self.value.try_lock() {
+            Ok(guard) => Some(guard),
+            Err(TryLockError::Poisoned(err)) => Some(err.into_inner()),
+            Err(TryLockError::WouldBlock) => None
+        }
+    }
+}
+
+impl Drop for AtomCell {
+    fn drop(&mut self) {
+        gc::atom_dropped();
+    }
+}
+
+//a mutable reference to a value that can be shared between threads
+//clones refer to the same value, two atoms are only equal if they are the same reference
+#[derive(Debug, Clone)]
+pub struct Atom {
+    cell: Arc<AtomCell>
+}
+
+impl Atom {
+    pub fn new(value: Value) -> Atom {
+        gc::atom_created();
+        Atom {
+            cell: Arc::new(AtomCell {
+                value: Mutex::new(value),
+                tracked: AtomicBool::new(false)
+            })
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Value> {
+        self.cell.lock()
+    }
+
+    //the cell for the garbage collector, see gc
+    pub fn cell(&self) -> Weak<AtomCell> {
+        Arc::downgrade(&self.cell)
+    }
+
+    pub fn get(&self) -> Value {
+        self.lock().clone()
+    }
+
+    pub fn set(&self, value: Value) {
+        let container = gc::is_container(&value);
+        *self.lock() = value;
+        self.track(container);
+    }
+
+    //sets the new value only if the current value is equal to the expected one
+    pub fn compare_and_set(&self, expected: &Value, value: Value) -> bool {
+        let container = gc::is_container(&value);
+        {
+            let mut guard = self.lock();
+            if *guard != *expected {
+                return false;
+            }
+            *guard = value;
+        }
+        self.track(container);
+        true
+    }
+
+    //an atom that holds a value that can refer to it can be part of a cycle, the lock must not be held
+    fn track(&self, container: bool) {
+        if container && !self.cell.tracked.swap(true, AtomicOrdering::Relaxed) {
+            gc::track_atom(self.cell());
+        }
+    }
+
+    //applies the function to the current value and retries if another thread changed it in the meantime
+    //the function runs without holding the lock, so it can be called several times
+    pub fn swap<F>(&self, mut function: F) -> Result<Value, Error> where F: FnMut(Value) -> Result<Value, Error> {
+        loop {
+            let current = self.get();
+            let value = function(current.clone())?;
+            if self.compare_and_set(&current, value.clone()) {
+                return Ok(value);
+            }
+        }
+    }
+}
+
+impl PartialEq for Atom {
+    fn eq(&self, other: &Atom) -> bool {
+        Arc::ptr_eq(&self.cell, &other.cell)
+    }
+}
+
+impl PartialOrd for Atom {
+    fn partial_cmp(&self, other: &Atom) -> Option<Ordering> {
+        match self == other {
+            true => Some(Ordering::Equal),
+            false => None
+        }
+    }
+}
\ No newline at end of file
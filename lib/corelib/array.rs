@@ -0,0 +1,338 @@
+use list::List;
+use error::Error;
+use functions::invalid_types;
+use functions::resolve_forced_argument;
+use functions::resolve_two_forced_arguments;
+use functions::resolve_three_forced_arguments;
+use number::Number;
+use value::Value;
+use stack::Stack;
+
+//nested lists are used as arrays like in apl: a list of numbers is a vector, a list of vectors of the same length
+//is a matrix and so on, everything that is not a list is a scalar
+
+//the largest number of elements that reshape and iota create, larger arrays would need gigabytes of memory
+const MAX_ELEMENTS: usize = 1 << 24;
+
+//the length of every axis of a nested list, scalars have the shape ()
+pub fn shape(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let op_1 = resolve_forced_argument(list, stack, "shape")?;
+    Ok(integer_list(&shape_of(&op_1, "shape")?))
+}
+
+//the elements of the second argument in the shape of the first one, they are repeated if there are too few
+pub fn reshape(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "reshape")?;
+    let dims = match to_dims(&op_1) {
+        Some(dims) => dims,
+        None => {
+            invalid_types(vec!(&op_1, &op_2), "reshape")?;
+            return Ok(Value::Nil);
+        }
+    };
+    //the lists of every axis are built, even if a later axis has the length zero
+    let mut count: usize = 1;
+    for len in dims.iter() {
+        count = match count.checked_mul(*len) {
+            Some(count) if count <= MAX_ELEMENTS => count,
+            _ => return Err(Error::new_with_origin("reshape", format!("the shape {} has more than {} elements.", integer_list(&dims), MAX_ELEMENTS)))
+        };
+    }
+    let mut values = Vec::new();
+    ravel(op_2, &mut values);
+    if values.is_empty() && count > 0 {
+        return Err(Error::new_with_origin("reshape", format!("can not fill the shape {} with an empty list.", integer_list(&dims))));
+    }
+    let mut iter = values.into_iter().cycle();
+    Ok(build(&dims, &mut iter))
+}
+
+//all scalars of a nested list in one list
+pub fn flatten(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let op_1 = resolve_forced_argument(list, stack, "flatten")?;
+    let mut values = Vec::new();
+    ravel(op_1, &mut values);
+    Ok(Value::List(List::from_cells(values)))
+}
+
+//reverses the order of the axes, the rows of a matrix become its columns
+pub fn transpose(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let op_1 = resolve_forced_argument(list, stack, "transpose")?;
+    let dims = shape_of(&op_1, "transpose")?;
+    if dims.len() < 2 {
+        return Ok(op_1);
+    }
+    let mut values = Vec::new();
+    ravel(op_1, &mut values);
+    //the index of an element in the flat list grows by the stride if its index on the axis grows by one
+    let mut strides = vec!(1; dims.len());
+    for axis in (0..dims.len() - 1).rev() {
+        strides[axis] = strides[axis + 1] * dims[axis + 1];
+    }
+    let reversed = dims.iter().rev().cloned().collect::<Vec<_>>();
+    let mut transposed = Vec::with_capacity(values.len());
+    for index in 0..values.len() {
+        let mut rest = index;
+        let mut offset = 0;
+        for (axis, len) in reversed.iter().enumerate().rev() {
+            offset += (rest % len) * strides[dims.len() - 1 - axis];
+            rest /= len;
+        }
+        transposed.push(values[offset].clone());
+    }
+    let mut iter = transposed.into_iter();
+    Ok(build(&reversed, &mut iter))
+}
+
+//applies the lambda to every pair of scalars of the two arguments, the result has the axes of both
+#[allow(clippy::useless_format)]
+pub fn outer(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2, op_3) = resolve_three_forced_arguments(list, stack, "outer")?;
+    match op_1 {
+        Value::Lambda(lambda) => {
+            map_scalars(&op_2, &mut |x| map_scalars(&op_3, &mut |y| lambda.eval_with_trace(vec!(x.clone(), y.clone()), stack, format!("outer"))))
+        },
+        type_1 => {
+            invalid_types(vec!(&type_1, &op_2, &op_3), "outer")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//the integers from 0 to n [exclusive]
+pub fn iota(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let op_1 = resolve_forced_argument(list, stack, "iota")?;
+    match op_1 {
+        Value::Integer(n) if n > MAX_ELEMENTS as i64 => {
+            Err(Error::new_with_origin("iota", format!("the length can be at most {}, found {}.", MAX_ELEMENTS, n)))
+        },
+        Value::Integer(n) if n >= 0 => Ok(Value::List(List::from_cells((0..n).map(Value::Integer).collect()))),
+        Value::Integer(n) => Err(Error::new_with_origin("iota", format!("expected a non-negative length, found {}.", n))),
+        type_1 => {
+            invalid_types(vec!(&type_1), "iota")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//combines the elements along the axis with the lambda, the result has one axis less
+//for a matrix axis 0 combines the rows element-wise and axis 1 combines the elements of each row
+#[allow(clippy::useless_format)]
+pub fn reduce_axis(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2, op_3) = resolve_three_forced_arguments(list, stack, "reduce-axis")?;
+    match (op_1, op_2) {
+        (Value::Lambda(lambda), Value::Integer(axis)) => {
+            let rank = shape_of(&op_3, "reduce-axis")?.len();
+            if axis < 0 || axis as usize >= rank {
+                return Err(Error::new_with_origin("reduce-axis", format!("the axis {} does not exist in an array of rank {}.", axis, rank)));
+            }
+            reduce(op_3, axis as usize, &mut |x, y| lambda.eval_with_trace(vec!(x, y), stack, format!("reduce-axis")))
+        },
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2, &op_3), "reduce-axis")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//the intermediate results of reducing the first axis, for a sum of '(1 2 3) they are (1 3 6)
+#[allow(clippy::useless_format)]
+pub fn scan(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "scan")?;
+    match (op_1, op_2) {
+        (Value::Lambda(lambda), Value::List(list)) => {
+            let mut result: Vec<Value> = Vec::with_capacity(list.cells().len());
+            for elem in list.into_cells() {
+                let next = match result.last() {
+                    Some(acc) => zip_scalars(acc.clone(), elem, "scan", &mut |x, y| lambda.eval_with_trace(vec!(x, y), stack, format!("scan")))?,
+                    None => elem
+                };
+                result.push(next);
+            }
+            Ok(Value::List(List::from_cells(result)))
+        },
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2), "scan")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//moves the first n elements to the end, a negative n moves the last elements to the front
+pub fn rotate(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "rotate")?;
+    match (op_1, op_2) {
+        (Value::Integer(n), Value::List(list)) => {
+            let mut cells = list.into_cells();
+            if !cells.is_empty() {
+                let len = cells.len() as i64;
+                cells.rotate_left((((n % len) + len) % len) as usize);
+            }
+            Ok(Value::List(List::from_cells(cells)))
+        },
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2), "rotate")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//combines the values with the operation from left to right, used by the arithmetic builtins:
+//lists are combined element-wise and a number with every element of a list, (+ '(1 2) 10) is (11 12)
+//a single value is combined with the identity like in (- x) or (/ x)
+pub fn broadcast<F>(mut values: Vec<Value>, identity: Value, fn_name: &'static str, op: F) -> Result<Value, Error> where F: Fn(Number, Number) -> Result<Number, Error> {
+    if !values.iter().all(is_numeric) {
+        invalid_types(values.iter().collect(), fn_name)?;
+    }
+    if values.len() < 2 {
+        values.insert(0, identity);
+    }
+    let mut iter = values.into_iter();
+    let mut acc = iter.next().unwrap();
+    for value in iter {
+        acc = zip_scalars(acc, value, fn_name, &mut |x, y| {
+            Ok(op(Number::from_value(&x).unwrap(), Number::from_value(&y).unwrap())?.into_value())
+        })?;
+    }
+    Ok(acc)
+}
+
+#[allow(clippy::needless_borrowed_reference)]
+fn is_numeric(value: &Value) -> bool {
+    match value {
+        &Value::List(ref list) => list.cells().iter().all(is_numeric),
+        other => Number::from_value(other).is_some()
+    }
+}
+
+//the shape of a value, an error if the elements of a list do not all have the same shape
+#[allow(clippy::needless_borrowed_reference)]
+fn shape_of(value: &Value, fn_name: &'static str) -> Result<Vec<usize>, Error> {
+    match value {
+        &Value::List(ref list) => {
+            let mut inner: Option<Vec<usize>> = None;
+            for (index, elem) in list.cells().iter().enumerate() {
+                let elem_shape = shape_of(elem, fn_name)?;
+                match inner {
+                    Some(ref first) if *first != elem_shape => {
+                        return Err(Error::new_with_origin(fn_name, format!("the list is ragged, element {} has the shape {}, but element 0 has the shape {}.",
+                            index, integer_list(&elem_shape), integer_list(first))));
+                    },
+                    Some(_) => {},
+                    None => inner = Some(elem_shape)
+                }
+            }
+            let mut dims = vec!(list.cells().len());
+            dims.extend(inner.unwrap_or_default());
+            Ok(dims)
+        },
+        _ => Ok(Vec::new())
+    }
+}
+
+//a single non-negative integer or a list of them
+#[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+fn to_dims(value: &Value) -> Option<Vec<usize>> {
+    match value {
+        &Value::Integer(i) if i >= 0 => Some(vec!(i as usize)),
+        &Value::List(ref list) => list.cells().iter().map(|x| match x {
+            &Value::Integer(i) if i >= 0 => Some(i as usize),
+            _ => None
+        }).collect(),
+        _ => None
+    }
+}
+
+fn integer_list(values: &[usize]) -> Value {
+    Value::List(List::from_cells(values.iter().map(|x| Value::Integer(*x as i64)).collect()))
+}
+
+//appends all scalars in order
+fn ravel(value: Value, values: &mut Vec<Value>) {
+    match value {
+        Value::List(list) => {
+            for elem in list.into_cells() {
+                ravel(elem, values);
+            }
+        },
+        scalar => values.push(scalar)
+    }
+}
+
+//takes the elements of an array with the shape from the iterator, which has to have enough elements
+fn build<I>(dims: &[usize], iter: &mut I) -> Value where I: Iterator<Item=Value> {
+    match dims.split_first() {
+        Some((len, rest)) => Value::List(List::from_cells((0..*len).map(|_| build(rest, iter)).collect())),
+        None => iter.next().unwrap()
+    }
+}
+
+#[allow(clippy::needless_borrowed_reference)]
+fn map_scalars<F>(value: &Value, op: &mut F) -> Result<Value, Error> where F: FnMut(&Value) -> Result<Value, Error> {
+    match value {
+        &Value::List(ref list) => {
+            let mut result = Vec::with_capacity(list.cells().len());
+            for elem in list.cells() {
+                result.push(map_scalars(elem, op)?);
+            }
+            Ok(Value::List(List::from_cells(result)))
+        },
+        scalar => op(scalar)
+    }
+}
+
+//applies the operation to the scalars at the same positions, a scalar is paired with every element of a list
+fn zip_scalars<F>(value_1: Value, value_2: Value, fn_name: &'static str, op: &mut F) -> Result<Value, Error> where F: FnMut(Value, Value) -> Result<Value, Error> {
+    match (value_1, value_2) {
+        (Value::List(list_1), Value::List(list_2)) => {
+            if list_1.cells().len() != list_2.cells().len() {
+                return Err(Error::new_with_origin(fn_name, format!("can not combine lists of the lengths {} and {}.", list_1.cells().len(), list_2.cells().len())));
+            }
+            let mut result = Vec::with_capacity(list_1.cells().len());
+            for (x, y) in list_1.into_cells().into_iter().zip(list_2.into_cells()) {
+                result.push(zip_scalars(x, y, fn_name, op)?);
+            }
+            Ok(Value::List(List::from_cells(result)))
+        },
+        (Value::List(list), scalar) => {
+            let mut result = Vec::with_capacity(list.cells().len());
+            for x in list.into_cells() {
+                result.push(zip_scalars(x, scalar.clone(), fn_name, op)?);
+            }
+            Ok(Value::List(List::from_cells(result)))
+        },
+        (scalar, Value::List(list)) => {
+            let mut result = Vec::with_capacity(list.cells().len());
+            for y in list.into_cells() {
+                result.push(zip_scalars(scalar.clone(), y, fn_name, op)?);
+            }
+            Ok(Value::List(List::from_cells(result)))
+        },
+        (x, y) => op(x, y)
+    }
+}
+
+//reduces the axis of a rectangular array
+#[allow(clippy::useless_format)]
+fn reduce<F>(value: Value, axis: usize, op: &mut F) -> Result<Value, Error> where F: FnMut(Value, Value) -> Result<Value, Error> {
+    let cells = match value {
+        Value::List(list) => list.into_cells(),
+        scalar => return Ok(scalar)
+    };
+    if axis > 0 {
+        let mut result = Vec::with_capacity(cells.len());
+        for elem in cells {
+            result.push(reduce(elem, axis - 1, op)?);
+        }
+        return Ok(Value::List(List::from_cells(result)));
+    }
+    let mut iter = cells.into_iter();
+    let mut acc = match iter.next() {
+        Some(first) => first,
+        None => return Err(Error::new_with_origin("reduce-axis", format!("can not reduce an axis of length 0.")))
+    };
+    for elem in iter {
+        acc = zip_scalars(acc, elem, "reduce-axis", op)?;
+    }
+    Ok(acc)
+}
\ No newline at end of file
@@ -0,0 +1,74 @@
+use list::List;
+use error::Error;
+use functions::assert_min_length;
+use functions::invalid_types;
+use functions::resolve_argument;
+use functions::resolve_arguments;
+use functions::resolve_two_arguments;
+use functions::resolve_three_arguments;
+use atom::Atom;
+use value::Value;
+use stack::Stack;
+
+pub fn atom(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let op_1 = resolve_argument(list, stack, "atom")?;
+    Ok(Value::Atom(Atom::new(op_1)))
+}
+
+pub fn deref(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let op_1 = resolve_argument(list, stack, "deref")?;
+    match op_1 {
+        Value::Atom(atom) => Ok(atom.get()),
+        type_1 => {
+            invalid_types(vec!(&type_1), "deref")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+pub fn reset(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_arguments(list, stack, "reset!")?;
+    match op_1 {
+        Value::Atom(atom) => {
+            atom.set(op_2.clone());
+            Ok(op_2)
+        },
+        type_1 => {
+            invalid_types(vec!(&type_1, &op_2), "reset!")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//sets the atom to the result of (lambda value args...), the lambda is called again if another thread changed the value
+#[allow(clippy::useless_format)]
+pub fn swap(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 2, "swap!")?;
+    let mut args = resolve_arguments(list, stack, "swap!")?;
+    let extra_args = args.split_off(2);
+    match (args.remove(0), args.remove(0)) {
+        (Value::Atom(atom), Value::Lambda(lambda)) => {
+            atom.swap(|value| {
+                let mut params = vec!(value);
+                params.extend(extra_args.iter().cloned());
+                lambda.eval_with_trace(params, stack, format!("swap!"))
+            })
+        },
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2), "swap!")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//sets the atom to the new value if its current value is equal to the expected one
+pub fn compare_and_set(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2, op_3) = resolve_three_arguments(list, stack, "compare-and-set!")?;
+    match op_1 {
+        Value::Atom(atom) => Ok(Value::Boolean(atom.compare_and_set(&op_2, op_3))),
+        type_1 => {
+            invalid_types(vec!(&type_1, &op_2, &op_3), "compare-and-set!")?;
+            Ok(Value::Nil)
+        }
+    }
+}
\ No newline at end of file
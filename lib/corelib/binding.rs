@@ -0,0 +1,177 @@
+use list::List;
+use list::resolve;
+use list::is_quote;
+use error::Error;
+use functions::assert_min_length;
+use symbol::Symbol;
+use value::Value;
+use pattern::Pattern;
+use stack::Stack;
+use scope::Scope;
+
+//binds the values of all bindings in a new scope, the bindings do not see each other
+pub fn let_fn(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 2, "let")?;
+    let bindings = binding_list(list, "let")?;
+    let mut scope = Scope::new();
+    for (pattern, expr) in bindings {
+        let value = resolve(expr, stack, "let")?;
+        for (name, value) in destructure(&pattern, &value, "let")? {
+            scope.set_variable(name, value);
+        }
+    }
+    stack.push(scope);
+    let result = body(list, stack, "let");
+    stack.pop();
+    result
+}
+
+//binds the values one after another, every binding sees the ones before it
+pub fn let_star(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 2, "let*")?;
+    let bindings = binding_list(list, "let*")?;
+    stack.push(Scope::new());
+    let result = bind_in_scope(bindings, stack, "let*").and_then(|_| body(list, stack, "let*"));
+    stack.pop();
+    result
+}
+
+//all names are bound to nil before the values are evaluated, so that lambdas can call each other
+#[allow(clippy::needless_borrowed_reference)]
+pub fn letrec(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 2, "letrec")?;
+    let bindings = binding_list(list, "letrec")?;
+    let mut scope = Scope::new();
+    for &(ref pattern, _) in &bindings {
+        for name in pattern.names() {
+            scope.set_variable(name, Value::Nil);
+        }
+    }
+    stack.push(scope);
+    let result = bind_in_scope(bindings, stack, "letrec").and_then(|_| body(list, stack, "letrec"));
+    stack.pop();
+    result
+}
+
+//evaluates the body of the first clause whose pattern matches the value and whose guard is true
+//a clause is (pattern body...) or (pattern :when guard body...), clauses can be quoted
+#[allow(clippy::len_zero, clippy::needless_borrowed_reference, clippy::single_match, clippy::useless_format)]
+pub fn match_fn(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 1, "match")?;
+    let value = resolve(list.cells().get(1).unwrap().clone(), stack, "match")?;
+    for cell in list.cells().iter().skip(2) {
+        let clause = match unquote(cell) {
+            &Value::List(ref clause) if clause.cells().len() > 0 => clause,
+            other => {
+                return Err(Error::new_with_origin("match", format!("expected a clause of the form (pattern body...), found {}.", other)));
+            }
+        };
+        let pattern = match Pattern::from_value(clause.cells().first().unwrap()) {
+            Ok(pattern) => pattern,
+            Err(err) => return Err(Error::new_with_origin("match", err.message().clone()))
+        };
+        let mut bindings = Vec::new();
+        if !pattern.matches(&value, &mut bindings) {
+            continue;
+        }
+        let (guard, body_start) = match clause.cells().get(1) {
+            Some(&Value::Symbol(ref symbol)) if symbol == ":when" => match clause.cells().get(2) {
+                Some(guard) => (Some(guard.clone()), 3),
+                None => return Err(Error::new_with_origin("match", format!("expected a guard after :when.")))
+            },
+            _ => (None, 1)
+        };
+        //a clause that binds nothing does not need a scope
+        let scoped = bindings.len() > 0;
+        if scoped {
+            let mut scope = Scope::new();
+            for (name, value) in bindings {
+                scope.set_variable(name, value);
+            }
+            stack.push(scope);
+        }
+        else {
+            stack.enter();
+        }
+        let result = match guard.map(|x| resolve(x, stack, "match")) {
+            None | Some(Ok(Value::Boolean(true))) => body_cells(&clause.cells()[body_start..], stack, "match").map(Some),
+            Some(Ok(Value::Boolean(false))) => Ok(None),
+            Some(Ok(other)) => Err(Error::new_with_origin("match", format!("expected boolean as guard, found {}.", other.type_str()))),
+            Some(Err(err)) => Err(err)
+        };
+        stack.pop();
+        match result? {
+            Some(value) => return Ok(value),
+            None => {}
+        }
+    }
+    Err(Error::new_with_origin("match", format!("no pattern matches the value {}.", value)))
+}
+
+//evaluates the bindings in the topmost scope and stores their values there
+fn bind_in_scope(bindings: Vec<(Pattern, Value)>, stack: &mut Stack, fn_name: &'static str) -> Result<(), Error> {
+    for (pattern, expr) in bindings {
+        let value = resolve(expr, stack, fn_name)?;
+        for (name, value) in destructure(&pattern, &value, fn_name)? {
+            stack.set_last_variable(name, value);
+        }
+    }
+    Ok(())
+}
+
+fn destructure(pattern: &Pattern, value: &Value, fn_name: &'static str) -> Result<Vec<(Symbol, Value)>, Error> {
+    match pattern.destructure(value) {
+        Ok(bindings) => Ok(bindings),
+        Err(err) => Err(Error::new_with_origin(fn_name, err.message().clone()))
+    }
+}
+
+//the (pattern expression) pairs of the first argument, which can be quoted
+#[allow(clippy::needless_borrowed_reference)]
+fn binding_list(list: &List, fn_name: &'static str) -> Result<Vec<(Pattern, Value)>, Error> {
+    let cells = match unquote(list.cells().get(1).unwrap()) {
+        &Value::List(ref bindings) => bindings.cells().to_vec(),
+        &Value::Nil => Vec::new(),
+        other => return Err(Error::new_with_origin(fn_name, format!("expected a list of bindings, found {}.", other.type_str())))
+    };
+    let mut bindings = Vec::new();
+    for cell in cells {
+        match cell {
+            Value::List(ref binding) if binding.cells().len() == 2 => {
+                let pattern = match Pattern::from_value(binding.cells().first().unwrap()) {
+                    Ok(pattern) => pattern,
+                    Err(err) => return Err(Error::new_with_origin(fn_name, err.message().clone()))
+                };
+                bindings.push((pattern, binding.cells().get(1).unwrap().clone()));
+            },
+            other => {
+                return Err(Error::new_with_origin(fn_name, format!("expected a binding of the form (pattern value), found {}.", other)));
+            }
+        }
+    }
+    Ok(bindings)
+}
+
+//the quoted value of '(...), other values stay unchanged
+#[allow(clippy::needless_borrowed_reference)]
+fn unquote(value: &Value) -> &Value {
+    match value {
+        &Value::List(ref list) if list.cells().len() == 2 && is_quote(list.cells().first().unwrap()) => {
+            list.cells().get(1).unwrap()
+        },
+        other => other
+    }
+}
+
+//evaluates the expressions after the bindings, the last value is the result
+fn body(list: &List, stack: &mut Stack, fn_name: &'static str) -> Result<Value, Error> {
+    body_cells(&list.cells()[2..], stack, fn_name)
+}
+
+fn body_cells(cells: &[Value], stack: &mut Stack, fn_name: &'static str) -> Result<Value, Error> {
+    let mut retval = Value::Nil;
+    for cell in cells {
+        retval = resolve(cell.clone(), stack, fn_name)?;
+    }
+    Ok(retval)
+}
\ No newline at end of file
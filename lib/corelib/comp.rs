@@ -1,134 +1,42 @@
-use ::FLOAT;
+use std::cmp::Ordering;
+
 use list::List;
 use error::Error;
 use stack::Stack;
 use functions::invalid_types;
 use functions::resolve_argument;
 use functions::resolve_two_arguments;
+use functions::resolve_arguments;
+use functions::resolve_numbers;
+use functions::assert_min_length;
 use value::Value;
+use number;
+use number::Number;
 
+//true if all arguments are equal
 pub fn eq(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2) = resolve_two_arguments(list, stack, "eq")?;
-    let is_equal = match (op_1, op_2) {
-        (Value::Nil, Value::Nil) => true,
-        (Value::List(list), Value::List(list2)) => list == list2,
-        (Value::Float(f1), Value::Float(f2)) => f1 == f2,
-        (Value::Integer(i1), Value::Integer(i2)) => i1 == i2,
-        (Value::Symbol(s1), Value::Symbol(s2)) => s1 == s2,
-        (Value::Boolean(b1), Value::Boolean(b2)) => b1 == b2,
-        //(Value::Lambda(l1), Value::Lambda(l2)) => l1 == l2,
-        (type_1, type_2) => {
-            invalid_types(vec!(&type_1, &type_2), "eq")?;
-            false
-        }
-    };
-    Ok(Value::Boolean(is_equal))
+    Ok(Value::Boolean(all_equal(list, stack, "eq")?))
 }
 
+//true if not all arguments are equal
 pub fn ne(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2) = resolve_two_arguments(list, stack, "ne")?;
-    let is_equal = match (op_1, op_2) {
-        (Value::Nil, Value::Nil) => false,
-        (Value::List(list), Value::List(list2)) => list != list2,
-        (Value::Float(f1), Value::Float(f2)) => f1 != f2,
-        (Value::Integer(i1), Value::Integer(i2)) => i1 != i2,
-        (Value::Symbol(s1), Value::Symbol(s2)) => s1 != s2,
-        (Value::Boolean(b1), Value::Boolean(b2)) => b1 != b2,
-        //(Value::Lambda(l1), Value::Lambda(l2)) => l1 != l2,
-        (type_1, type_2) => {
-            invalid_types(vec!(&type_1, &type_2), "ne")?;
-            false
-        }
-    };
-    Ok(Value::Boolean(is_equal))
+    Ok(Value::Boolean(!all_equal(list, stack, "ne")?))
 }
 
 pub fn lt(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2) = resolve_two_arguments(list, stack, "lt")?;
-    match (op_1, op_2) {
-        (Value::Integer(i_1), Value::Integer(i_2)) => {
-            return Ok(Value::Boolean(i_1 < i_2));
-        },
-        (Value::Integer(i_1), Value::Float(f_2)) => {
-            return Ok(Value::Boolean((i_1 as FLOAT) < f_2));
-        },
-        (Value::Float(f_1), Value::Integer(i_2)) => {
-            return Ok(Value::Boolean(f_1 < i_2 as FLOAT));
-        },
-        (Value::Float(f_1), Value::Float(f_2)) => {
-            return Ok(Value::Boolean(f_1 < f_2));
-        },
-        (type_1, type_2) => {
-            invalid_types(vec!(&type_1, &type_2), "lt")?;
-        }
-    };
-    Ok(Value::Nil)
+    compare_op(list, stack, "lt", |x| x == Ordering::Less)
 }
 
 pub fn gt(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2) = resolve_two_arguments(list, stack, "gt")?;
-    match (op_1, op_2) {
-        (Value::Integer(i_1), Value::Integer(i_2)) => {
-            return Ok(Value::Boolean(i_1 > i_2));
-        },
-        (Value::Integer(i_1), Value::Float(f_2)) => {
-            return Ok(Value::Boolean(i_1 as FLOAT > f_2));
-        },
-        (Value::Float(f_1), Value::Integer(i_2)) => {
-            return Ok(Value::Boolean(f_1 > i_2 as FLOAT));
-        },
-        (Value::Float(f_1), Value::Float(f_2)) => {
-            return Ok(Value::Boolean(f_1 > f_2));
-        },
-        (type_1, type_2) => {
-            invalid_types(vec!(&type_1, &type_2), "gt")?;
-        }
-    };
-    Ok(Value::Nil)
+    compare_op(list, stack, "gt", |x| x == Ordering::Greater)
 }
 
 pub fn le(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2) = resolve_two_arguments(list, stack, "le")?;
-    match (op_1, op_2) {
-        (Value::Integer(i_1), Value::Integer(i_2)) => {
-            return Ok(Value::Boolean(i_1 <= i_2));
-        },
-        (Value::Integer(i_1), Value::Float(f_2)) => {
-            return Ok(Value::Boolean(i_1 as FLOAT <= f_2));
-        },
-        (Value::Float(f_1), Value::Integer(i_2)) => {
-            return Ok(Value::Boolean(f_1 <= i_2 as FLOAT));
-        },
-        (Value::Float(f_1), Value::Float(f_2)) => {
-            return Ok(Value::Boolean(f_1 <= f_2));
-        },
-        (type_1, type_2) => {
-            invalid_types(vec!(&type_1, &type_2), "le")?;
-        }
-    };
-    Ok(Value::Nil)    
+    compare_op(list, stack, "le", |x| x != Ordering::Greater)
 }
 
 pub fn ge(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2) = resolve_two_arguments(list, stack, "ge")?;
-    match (op_1, op_2) {
-        (Value::Integer(i_1), Value::Integer(i_2)) => {
-            return Ok(Value::Boolean(i_1 >= i_2));
-        },
-        (Value::Integer(i_1), Value::Float(f_2)) => {
-            return Ok(Value::Boolean(i_1 as FLOAT >= f_2));
-        },
-        (Value::Float(f_1), Value::Integer(i_2)) => {
-            return Ok(Value::Boolean(f_1 >= i_2 as FLOAT));
-        },
-        (Value::Float(f_1), Value::Float(f_2)) => {
-            return Ok(Value::Boolean(f_1 >= f_2));
-        },
-        (type_1, type_2) => {
-            invalid_types(vec!(&type_1, &type_2), "ge")?;
-        }
-    };
-    Ok(Value::Nil)
+    compare_op(list, stack, "ge", |x| x != Ordering::Less)
 }
 
 pub fn and(list: &List, stack: &mut Stack) -> Result<Value, Error> {
@@ -168,4 +76,166 @@ pub fn not(list: &List, stack: &mut Stack) -> Result<Value, Error> {
         }
     };
     Ok(Value::Nil)
+}
+
+//-1, 0 or 1 depending on the order of two values of any type, see total_order
+pub fn compare(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_arguments(list, stack, "compare")?;
+    Ok(Value::Integer(match total_order(&op_1, &op_2) {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1
+    }))
+}
+
+//tests the ordering of each number and the next, comparisons with nan are always false
+fn compare_op(list: &List, stack: &mut Stack, name: &'static str, test: fn(Ordering) -> bool) -> Result<Value, Error> {
+    assert_min_length(list, 1, name)?;
+    let numbers = resolve_numbers(list, stack, name)?;
+    for pair in numbers.windows(2) {
+        if !number::compare(&pair[0], &pair[1]).map(test).unwrap_or(false) {
+            return Ok(Value::Boolean(false));
+        }
+    }
+    Ok(Value::Boolean(true))
+}
+
+fn all_equal(list: &List, stack: &mut Stack, name: &'static str) -> Result<bool, Error> {
+    assert_min_length(list, 1, name)?;
+    let values = resolve_arguments(list, stack, name)?;
+    let mut result = true;
+    for pair in values.windows(2) {
+        result = result && equal(&pair[0], &pair[1], name)?;
+    }
+    Ok(result)
+}
+
+//numbers of different types are equal if their values are, 1/2 equals 0.5
+#[allow(clippy::needless_borrowed_reference, clippy::single_match)]
+fn equal(value_1: &Value, value_2: &Value, name: &'static str) -> Result<bool, Error> {
+    match (Number::from_value(value_1), Number::from_value(value_2)) {
+        (Some(n_1), Some(n_2)) => return Ok(number::compare(&n_1, &n_2) == Some(Ordering::Equal)),
+        _ => {}
+    }
+    let is_equal = match (value_1, value_2) {
+        (&Value::Nil, &Value::Nil) => true,
+        (&Value::List(_), &Value::List(_)) => inner_equal(value_1, value_2),
+        (&Value::Symbol(ref s1), &Value::Symbol(ref s2)) => s1 == s2,
+        (&Value::Boolean(b1), &Value::Boolean(b2)) => b1 == b2,
+        (&Value::Char(c1), &Value::Char(c2)) => c1 == c2,
+        (&Value::Map(_), &Value::Map(_)) => inner_equal(value_1, value_2),
+        (&Value::Atom(ref a1), &Value::Atom(ref a2)) => a1 == a2,
+        (&Value::Future(ref f1), &Value::Future(ref f2)) => f1 == f2,
+        (&Value::Record(_), &Value::Record(_)) => inner_equal(value_1, value_2),
+        //(Value::Lambda(l1), Value::Lambda(l2)) => l1 == l2,
+        (type_1, type_2) => {
+            invalid_types(vec!(type_1, type_2), name)?;
+            false
+        }
+    };
+    Ok(is_equal)
+}
+
+//equal for the elements of lists, the values of maps and the fields of records, so '(1) equals '(1.0)
+//map keys are still compared by their type, values of different types are not equal
+#[allow(clippy::needless_borrowed_reference, clippy::single_match)]
+fn inner_equal(value_1: &Value, value_2: &Value) -> bool {
+    match (Number::from_value(value_1), Number::from_value(value_2)) {
+        (Some(n_1), Some(n_2)) => return number::compare(&n_1, &n_2) == Some(Ordering::Equal),
+        _ => {}
+    }
+    match (value_1, value_2) {
+        (&Value::List(ref list_1), &Value::List(ref list_2)) => {
+            list_1.cells().len() == list_2.cells().len()
+                && list_1.cells().iter().zip(list_2.cells().iter()).all(|(x, y)| inner_equal(x, y))
+        },
+        (&Value::Map(ref map_1), &Value::Map(ref map_2)) => {
+            map_1.len() == map_2.len()
+                && map_1.entries().iter().all(|x| map_2.get(&x.0).map(|y| inner_equal(&x.1, y)).unwrap_or(false))
+        },
+        (&Value::Record(ref record_1), &Value::Record(ref record_2)) => {
+            record_1.name() == record_2.name() && record_1.fields().len() == record_2.fields().len()
+                && record_1.fields().iter().zip(record_2.fields().iter()).all(|(x, y)| x.0 == y.0 && inner_equal(&x.1, &y.1))
+        },
+        _ => value_1 == value_2
+    }
+}
+
+//an order over all values, used for sorting:
+//values of different types are ordered by their type, numbers by their value with nan after all other numbers,
+//lists [and strings] and maps lexicographically, records by their name and fields
+//values that can not be ordered, like lambdas, are equal to each other
+#[allow(clippy::needless_borrowed_reference, clippy::single_match)]
+pub fn total_order(value_1: &Value, value_2: &Value) -> Ordering {
+    match (Number::from_value(value_1), Number::from_value(value_2)) {
+        (Some(n_1), Some(n_2)) => {
+            return match number::compare(&n_1, &n_2) {
+                Some(ordering) => ordering,
+                None => is_nan(&n_1).cmp(&is_nan(&n_2))
+            };
+        },
+        _ => {}
+    }
+    match (value_1, value_2) {
+        (&Value::Boolean(b1), &Value::Boolean(b2)) => b1.cmp(&b2),
+        (&Value::Char(c1), &Value::Char(c2)) => c1.cmp(&c2),
+        (&Value::Symbol(ref s1), &Value::Symbol(ref s2)) => s1.cmp(s2),
+        (&Value::List(_), _) | (&Value::Nil, _) if type_rank(value_2) == 4 => {
+            lexicographic(list_cells(value_1).iter(), list_cells(value_2).iter(), total_order)
+        },
+        (&Value::Map(ref m1), &Value::Map(ref m2)) => {
+            lexicographic(m1.entries().iter(), m2.entries().iter(), |e1, e2| total_order(&e1.0, &e2.0).then_with(|| total_order(&e1.1, &e2.1)))
+        },
+        (&Value::Record(ref r1), &Value::Record(ref r2)) => {
+            r1.name().cmp(r2.name()).then_with(|| lexicographic(r1.fields().iter(), r2.fields().iter(), |f1, f2| f1.0.cmp(&f2.0).then_with(|| total_order(&f1.1, &f2.1))))
+        },
+        (value_1, value_2) => type_rank(value_1).cmp(&type_rank(value_2))
+    }
+}
+
+fn is_nan(n: &Number) -> bool {
+    match n {
+        &Number::Float(f) => f.is_nan(),
+        _ => false
+    }
+}
+
+fn type_rank(value: &Value) -> u8 {
+    match value {
+        &Value::Boolean(_) => 0,
+        &Value::Integer(_) | &Value::BigInt(_) | &Value::Ratio(_) | &Value::Float(_) => 1,
+        &Value::Char(_) => 2,
+        &Value::Symbol(_) | &Value::Variable(_) => 3,
+        &Value::Nil | &Value::List(_) => 4,
+        &Value::Map(_) => 5,
+        &Value::Record(_) => 6,
+        &Value::LazySeq(_) => 7,
+        &Value::Atom(_) => 8,
+        &Value::Future(_) => 9,
+        &Value::Lambda(_) => 10
+    }
+}
+
+//nil is the empty list
+#[allow(clippy::needless_borrowed_reference)]
+fn list_cells(value: &Value) -> &[Value] {
+    match value {
+        &Value::List(ref list) => list.cells(),
+        _ => &[]
+    }
+}
+
+//compares the elements until they differ, a shorter sequence that is a prefix of the other is smaller
+fn lexicographic<'a, T: 'a, I, F>(mut iter_1: I, mut iter_2: I, compare: F) -> Ordering where I: Iterator<Item=&'a T>, F: Fn(&T, &T) -> Ordering {
+    loop {
+        match (iter_1.next(), iter_2.next()) {
+            (Some(e1), Some(e2)) => match compare(e1, e2) {
+                Ordering::Equal => {},
+                ordering => return ordering
+            },
+            (Some(_), None) => return Ordering::Greater,
+            (None, Some(_)) => return Ordering::Less,
+            (None, None) => return Ordering::Equal
+        }
+    }
 }
\ No newline at end of file
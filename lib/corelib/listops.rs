@@ -1,17 +1,27 @@
-use std::collections::BTreeSet;
+use std::cmp::Ordering;
 
 use ::INT;
 use list::List;
 use error::Error;
+use functions::assert_min_length;
+use functions::force;
 use functions::invalid_types;
-use functions::resolve_argument;
+use functions::resolve_forced_arguments;
 use functions::resolve_two_arguments;
-use functions::resolve_three_arguments;
+use functions::resolve_forced_argument;
+use functions::resolve_two_forced_arguments;
+use functions::resolve_three_forced_arguments;
 use value::Value;
+use lambda::Lambda;
+use map::Map;
+use number;
+use number::Number;
+use corelib::comp::total_order;
 use stack::Stack;
 
+#[allow(clippy::len_zero)]
 pub fn last(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let op_1 = resolve_argument(list, stack, "last")?;
+    let op_1 = resolve_forced_argument(list, stack, "last")?;
     match op_1 {
         Value::List(list) => {
             if list.cells().len() > 0 {
@@ -29,14 +39,13 @@ pub fn last(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     Ok(Value::Nil)
 }
 
+#[allow(clippy::len_zero)]
 pub fn init(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let op_1 = resolve_argument(list, stack, "init")?;
+    let op_1 = resolve_forced_argument(list, stack, "init")?;
     match op_1 {
-        Value::List(mut list) => {
+        Value::List(list) => {
             if list.cells().len() > 0 {
-                let len = list.cells().len();
-                list.cells_mut().remove(len - 1);
-                return Ok(Value::List(list));
+                return Ok(Value::List(list.init()));
             }
             else {
                 return Ok(Value::Nil);
@@ -49,13 +58,13 @@ pub fn init(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     Ok(Value::Nil)
 }
 
+#[allow(clippy::len_zero)]
 pub fn tail(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let op_1 = resolve_argument(list, stack, "tail")?;
+    let op_1 = resolve_forced_argument(list, stack, "tail")?;
     match op_1 {
-        Value::List(mut list) => {
+        Value::List(list) => {
             if list.cells().len() > 0 {
-                list.cells_mut().remove(0);
-                return Ok(Value::List(list));
+                return Ok(Value::List(list.tail()));
             }
             else {
                 return Ok(Value::Nil);
@@ -69,11 +78,14 @@ pub fn tail(list: &List, stack: &mut Stack) -> Result<Value, Error> {
 }
 
 pub fn len(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let op_1 = resolve_argument(list, stack, "len")?;
+    let op_1 = resolve_forced_argument(list, stack, "len")?;
     match op_1 {
         Value::List(list) => {
             return Ok(Value::Integer(list.cells().len() as INT));
         },
+        Value::Map(map) => {
+            return Ok(Value::Integer(map.len() as INT));
+        },
         type_1 => {
             invalid_types(vec!(&type_1), "len")?;
         }
@@ -81,9 +93,16 @@ pub fn len(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     Ok(Value::Nil)
 }
 
+#[allow(clippy::len_zero, clippy::useless_format)]
 pub fn nth(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     let (op_1, op_2) = resolve_two_arguments(list, stack, "nth")?;
-    match (op_1, op_2) {
+    //only the elements of a lazy sequence up to the index are computed
+    let op_2 = match (&op_1, op_2) {
+        (&Value::Integer(index), Value::LazySeq(seq)) if index >= 0 => return Ok(seq.nth(index as usize, stack)?.unwrap_or(Value::Nil)),
+        (&Value::Integer(_), Value::LazySeq(_)) => return Err(Error::new_with_origin("nth", format!("index must be non-negative."))),
+        (_, op_2) => force(op_2, stack)?
+    };
+    match (force(op_1, stack)?, op_2) {
         (Value::Integer(index), Value::List(list)) => {
             if index < 0 {
                 return Err(Error::new_with_origin("nth", format!("index must be non-negative.")));
@@ -101,11 +120,10 @@ pub fn nth(list: &List, stack: &mut Stack) -> Result<Value, Error> {
 }
 
 pub fn cons(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2) = resolve_two_arguments(list, stack, "cons")?;
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "cons")?;
     match (op_1, op_2) {
-        (val, Value::List(mut list)) => {
-            list.cells_mut().insert(0, val);
-            return Ok(Value::List(list));
+        (val, Value::List(list)) => {
+            return Ok(Value::List(list.cons(val)));
         },
         (type_1, type_2) => {
             invalid_types(vec!(&type_1, &type_2), "cons")?;
@@ -115,7 +133,7 @@ pub fn cons(list: &List, stack: &mut Stack) -> Result<Value, Error> {
 }
 
 pub fn append(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2) = resolve_two_arguments(list, stack, "append")?;
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "append")?;
     match (op_1, op_2) {
         (Value::List(list_1), Value::List(list_2)) => {
             let mut cells = list_1.into_cells();
@@ -130,7 +148,7 @@ pub fn append(list: &List, stack: &mut Stack) -> Result<Value, Error> {
 }
 
 pub fn unique(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let op_1 = resolve_argument(list, stack, "unique")?;
+    let op_1 = resolve_forced_argument(list, stack, "unique")?;
     match op_1 {
         Value::List(list) => {
             let mut new_list = Vec::new();
@@ -148,16 +166,24 @@ pub fn unique(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     Ok(Value::Nil)
 }
 
+//with more than one list the lambda gets an element of every list
+#[allow(clippy::useless_format)]
 pub fn map(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    if list.cells().len() > 3 {
+        return map_lists(list, stack);
+    }
     let (op_1, op_2) = resolve_two_arguments(list, stack, "map")?;
     match (op_1, op_2) {
-        (Value::Lambda(mut lambda), Value::List(list)) => {
+        (Value::Lambda(lambda), Value::List(list)) => {
             let mut result = Vec::new();
             for value in list.into_cells().into_iter() {
                 result.push(lambda.eval_with_trace(vec!(value), stack, format!("map"))?);
             }
             return Ok(Value::List(List::from_cells(result)));
         },
+        (Value::Lambda(lambda), Value::LazySeq(seq)) => {
+            return Ok(Value::LazySeq(seq.map(lambda)));
+        },
         (type_1, type_2) => {
             invalid_types(vec!(&type_1, &type_2), "map")?;
         }
@@ -165,10 +191,11 @@ pub fn map(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     Ok(Value::Nil)
 }
 
+#[allow(clippy::len_zero, clippy::useless_format)]
 pub fn fold(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2, op_3) = resolve_three_arguments(list, stack, "fold")?;
+    let (op_1, op_2, op_3) = resolve_three_forced_arguments(list, stack, "fold")?;
     match (op_1, op_2, op_3) {
-        (first, Value::Lambda(mut lambda), Value::List(list)) => {
+        (first, Value::Lambda(lambda), Value::List(list)) => {
             let mut acc;
             if list.cells().len() == 0 {
                 return Ok(Value::Nil);
@@ -186,10 +213,11 @@ pub fn fold(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     Ok(Value::Nil)
 }
 
+#[allow(clippy::len_zero, clippy::useless_format)]
 pub fn expand(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2, op_3) = resolve_three_arguments(list, stack, "expand")?;
+    let (op_1, op_2, op_3) = resolve_three_forced_arguments(list, stack, "expand")?;
     match (op_1, op_2, op_3) {
-        (first, Value::Lambda(mut lambda), Value::List(list)) => {
+        (first, Value::Lambda(lambda), Value::List(list)) => {
             let mut acc = Vec::new();
             if list.cells().len() == 0 {
                 return Ok(Value::Nil);
@@ -209,10 +237,11 @@ pub fn expand(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     Ok(Value::Nil)
 }
 
+#[allow(clippy::explicit_counter_loop, clippy::useless_format)]
 pub fn any(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2) = resolve_two_arguments(list, stack, "any")?;
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "any")?;
     match (op_1, op_2) {
-        (Value::Lambda(mut lambda), Value::List(list)) => {
+        (Value::Lambda(lambda), Value::List(list)) => {
             let mut result = false;
             let mut index_counter = 0;
             for elem in list.into_cells().into_iter() {
@@ -237,10 +266,11 @@ pub fn any(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     Ok(Value::Nil)
 }
 
+#[allow(clippy::explicit_counter_loop, clippy::len_zero, clippy::useless_format)]
 pub fn all(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2) = resolve_two_arguments(list, stack, "all")?;
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "all")?;
     match (op_1, op_2) {
-        (Value::Lambda(mut lambda), Value::List(list)) => {
+        (Value::Lambda(lambda), Value::List(list)) => {
             let mut result = true;
             if list.cells().len() == 0 {
                 return Ok(Value::Boolean(false));
@@ -268,10 +298,11 @@ pub fn all(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     Ok(Value::Nil)
 }
 
+#[allow(clippy::explicit_counter_loop, clippy::useless_format)]
 pub fn filter(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     let (op_1, op_2) = resolve_two_arguments(list, stack, "filter")?;
     match (op_1, op_2) {
-        (Value::Lambda(mut lambda), Value::List(list)) => {
+        (Value::Lambda(lambda), Value::List(list)) => {
             let mut new_list = Vec::new();
             let mut index_counter = 0;
             for value in list.into_cells().into_iter() {
@@ -289,6 +320,9 @@ pub fn filter(list: &List, stack: &mut Stack) -> Result<Value, Error> {
             }
             return Ok(Value::List(List::from_cells(new_list)));
         },
+        (Value::Lambda(lambda), Value::LazySeq(seq)) => {
+            return Ok(Value::LazySeq(seq.filter(lambda)));
+        },
         (type_1, type_2) => {
             invalid_types(vec!(&type_1, &type_2), "filter")?;
         }
@@ -296,8 +330,9 @@ pub fn filter(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     Ok(Value::Nil)
 }
 
+#[allow(clippy::explicit_counter_loop)]
 pub fn find(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2) = resolve_two_arguments(list, stack, "find")?;
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "find")?;
     match (op_1, op_2) {
         (value, Value::List(list)) => {
             let mut counter = 0;
@@ -317,7 +352,7 @@ pub fn find(list: &List, stack: &mut Stack) -> Result<Value, Error> {
 }
 
 pub fn split_at(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2) = resolve_two_arguments(list, stack, "split_at")?;
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "split_at")?;
     match (op_1, op_2) {
         (Value::Integer(int_32), Value::List(list)) => {
             if 0 <= int_32 && int_32 <= (list.cells().len() - 1) as INT {
@@ -337,7 +372,7 @@ pub fn split_at(list: &List, stack: &mut Stack) -> Result<Value, Error> {
 }
 
 pub fn combine(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2) = resolve_two_arguments(list, stack, "combine")?;
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "combine")?;
     match (op_1, op_2) {
         (Value::List(list_1), Value::List(list_2)) => {
             let cells_1 = list_1.into_cells();
@@ -358,7 +393,7 @@ pub fn combine(list: &List, stack: &mut Stack) -> Result<Value, Error> {
 }
 
 pub fn intersect(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2) = resolve_two_arguments(list, stack, "intersect")?;
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "intersect")?;
     match (op_1, op_2) {
         (Value::List(list_1), Value::List(list_2)) => {
             let cells_1 = list_1.into_cells();
@@ -380,8 +415,9 @@ pub fn intersect(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     Ok(Value::Nil)
 }
 
+#[allow(clippy::explicit_counter_loop)]
 pub fn zip(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2) = resolve_two_arguments(list, stack, "zip")?;
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "zip")?;
     match (op_1, op_2) {
         (Value::List(list_1), Value::List(list_2)) => {
             let cells_1 = list_1.into_cells();
@@ -403,7 +439,7 @@ pub fn zip(list: &List, stack: &mut Stack) -> Result<Value, Error> {
 }
 
 pub fn rev(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let op_1 = resolve_argument(list, stack, "rev")?;
+    let op_1 = resolve_forced_argument(list, stack, "rev")?;
     match op_1 {
         Value::List(list) => {
             return Ok(Value::List(List::from_cells(list.into_cells().into_iter().rev().collect::<Vec<Value>>())));
@@ -415,34 +451,462 @@ pub fn rev(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     Ok(Value::Nil)
 }
 
-// TODO: f32 doesnt implement Ord, so I have to find another way to sort lists consisting of ints/floats
-pub fn sort(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let op_1 = resolve_argument(list, stack, "sort")?;
+//calls the lambda with the index and the element for every element of the list
+#[allow(clippy::useless_format)]
+pub fn map_indexed(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "map-indexed")?;
+    match (op_1, op_2) {
+        (Value::Lambda(lambda), Value::List(list)) => {
+            let mut result = Vec::with_capacity(list.cells().len());
+            for (index, value) in list.into_cells().into_iter().enumerate() {
+                result.push(lambda.eval_with_trace(vec!(Value::Integer(index as INT), value), stack, format!("map-indexed"))?);
+            }
+            Ok(Value::List(List::from_cells(result)))
+        },
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2), "map-indexed")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//folds the list from the right, the lambda gets the element and the accumulator
+#[allow(clippy::useless_format)]
+pub fn fold_right(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2, op_3) = resolve_three_forced_arguments(list, stack, "fold-right")?;
+    match (op_1, op_2, op_3) {
+        (first, Value::Lambda(lambda), Value::List(list)) => {
+            let mut acc = first;
+            for elem in list.into_cells().into_iter().rev() {
+                acc = lambda.eval_with_trace(vec!(elem, acc), stack, format!("fold-right"))?;
+            }
+            Ok(acc)
+        },
+        (type_1, type_2, type_3) => {
+            invalid_types(vec!(&type_1, &type_2, &type_3), "fold-right")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//a map from the values the lambda returns to the lists of elements with that value, in the order of the first occurence
+#[allow(clippy::useless_format)]
+pub fn group_by(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "group-by")?;
+    match (op_1, op_2) {
+        (Value::Lambda(lambda), Value::List(list)) => {
+            let mut groups: Vec<(Value, Vec<Value>)> = Vec::new();
+            for elem in list.into_cells() {
+                let key = lambda.eval_with_trace(vec!(elem.clone()), stack, format!("group-by"))?;
+                match groups.iter().position(|x| x.0 == key) {
+                    Some(index) => groups[index].1.push(elem),
+                    None => groups.push((key, vec!(elem)))
+                }
+            }
+            let mut map = Map::new();
+            for (key, values) in groups {
+                map.insert(key, Value::List(List::from_cells(values)));
+            }
+            Ok(Value::Map(map))
+        },
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2), "group-by")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//a list of the elements for which the lambda returns true and a list of the others
+pub fn partition(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "partition")?;
+    match (op_1, op_2) {
+        (Value::Lambda(lambda), Value::List(list)) => {
+            let mut matching = Vec::new();
+            let mut others = Vec::new();
+            for (index, elem) in list.into_cells().into_iter().enumerate() {
+                match test(&lambda, &elem, index, stack, "partition")? {
+                    true => matching.push(elem),
+                    false => others.push(elem)
+                }
+            }
+            Ok(Value::List(List::from_cells(vec!(Value::List(List::from_cells(matching)), Value::List(List::from_cells(others))))))
+        },
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2), "partition")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//splits the list every time the lambda returns a different value than for the previous element
+#[allow(clippy::useless_format)]
+pub fn partition_by(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "partition-by")?;
+    match (op_1, op_2) {
+        (Value::Lambda(lambda), Value::List(list)) => {
+            let mut parts = Vec::new();
+            let mut current = Vec::new();
+            let mut last_key = None;
+            for elem in list.into_cells() {
+                let key = lambda.eval_with_trace(vec!(elem.clone()), stack, format!("partition-by"))?;
+                if last_key.is_some() && last_key.as_ref() != Some(&key) {
+                    parts.push(Value::List(List::from_cells(::std::mem::take(&mut current))));
+                }
+                current.push(elem);
+                last_key = Some(key);
+            }
+            if !current.is_empty() {
+                parts.push(Value::List(List::from_cells(current)));
+            }
+            Ok(Value::List(List::from_cells(parts)))
+        },
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2), "partition-by")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//splits the list into lists of n elements, the last one can be shorter
+pub fn chunk(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (size, cells) = size_and_list(list, stack, "chunk")?;
+    Ok(Value::List(List::from_cells(cells.chunks(size).map(|x| Value::List(List::from_cells(x.to_vec()))).collect())))
+}
+
+//all sublists of n consecutive elements, empty if the list is shorter than n
+pub fn windows(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (size, cells) = size_and_list(list, stack, "windows")?;
+    Ok(Value::List(List::from_cells(cells.windows(size).map(|x| Value::List(List::from_cells(x.to_vec()))).collect())))
+}
+
+//the elements after the first one for which the lambda returns false, lazy for lazy sequences
+pub fn drop_while(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_arguments(list, stack, "drop-while")?;
+    match (op_1, op_2) {
+        (Value::Lambda(lambda), Value::LazySeq(seq)) => Ok(Value::LazySeq(seq.drop_while(lambda))),
+        (Value::Lambda(lambda), Value::List(list)) => {
+            let mut cells = list.into_cells();
+            let mut count = 0;
+            while count < cells.len() && test(&lambda, &cells[count], count, stack, "drop-while")? {
+                count += 1;
+            }
+            Ok(Value::List(List::from_cells(cells.split_off(count))))
+        },
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2), "drop-while")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//maps the elements to lists and appends them
+#[allow(clippy::useless_format)]
+pub fn flat_map(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "flat-map")?;
+    match (op_1, op_2) {
+        (Value::Lambda(lambda), Value::List(list)) => {
+            let mut result = Vec::new();
+            for elem in list.into_cells() {
+                let mapped = lambda.eval_with_trace(vec!(elem), stack, format!("flat-map"))?;
+                match force(mapped, stack)? {
+                    Value::List(mapped) => result.extend(mapped.into_cells()),
+                    other => return Err(Error::new_with_origin("flat-map", format!("expected the lambda to return a list, found {}.", other.type_str())))
+                }
+            }
+            Ok(Value::List(List::from_cells(result)))
+        },
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2), "flat-map")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//the first elements of all lists, then the second ones and so on until the shortest list ends
+pub fn interleave(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 1, "interleave")?;
+    let lists = into_lists(resolve_forced_arguments(list, stack, "interleave")?, "interleave")?;
+    let len = lists.iter().map(|x| x.len()).min().unwrap();
+    let mut result = Vec::with_capacity(len * lists.len());
+    for index in 0..len {
+        for cells in &lists {
+            result.push(cells[index].clone());
+        }
+    }
+    Ok(Value::List(List::from_cells(result)))
+}
+
+//a map from the elements to the number of times they occur in the list
+pub fn frequencies(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let op_1 = resolve_forced_argument(list, stack, "frequencies")?;
     match op_1 {
         Value::List(list) => {
-            let mut sorted = BTreeSet::new();
-            let cells = list.into_cells();
-            for elem in cells.into_iter() {
-                match elem {
-                    Value::Integer(int) => {
-                        sorted.insert(int);
-                    },
-                    type_2 => {
-                        return Err(Error::new_with_origin("sort", format!("only lists that contain integers can be sorted. found {}.", type_2.type_str())))
+            let mut counts: Vec<(Value, INT)> = Vec::new();
+            for elem in list.into_cells() {
+                match counts.iter().position(|x| x.0 == elem) {
+                    Some(index) => counts[index].1 += 1,
+                    None => counts.push((elem, 1))
+                }
+            }
+            let mut map = Map::new();
+            for (key, count) in counts {
+                map.insert(key, Value::Integer(count));
+            }
+            Ok(Value::Map(map))
+        },
+        type_1 => {
+            invalid_types(vec!(&type_1), "frequencies")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//the element for which the lambda returns the smallest value, compared like in 'sort'
+pub fn min_by(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    extremum_by(list, stack, "min-by", Ordering::Less)
+}
+
+//the element for which the lambda returns the greatest value, compared like in 'sort'
+pub fn max_by(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    extremum_by(list, stack, "max-by", Ordering::Greater)
+}
+
+//the first element for every value the lambda returns
+#[allow(clippy::useless_format)]
+pub fn distinct_by(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "distinct-by")?;
+    match (op_1, op_2) {
+        (Value::Lambda(lambda), Value::List(list)) => {
+            let mut keys = Vec::new();
+            let mut result = Vec::new();
+            for elem in list.into_cells() {
+                let key = lambda.eval_with_trace(vec!(elem.clone()), stack, format!("distinct-by"))?;
+                if !keys.contains(&key) {
+                    keys.push(key);
+                    result.push(elem);
+                }
+            }
+            Ok(Value::List(List::from_cells(result)))
+        },
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2), "distinct-by")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//removes elements that are equal to the previous one
+pub fn dedupe(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let op_1 = resolve_forced_argument(list, stack, "dedupe")?;
+    match op_1 {
+        Value::List(list) => {
+            let mut cells = list.into_cells();
+            cells.dedup();
+            Ok(Value::List(List::from_cells(cells)))
+        },
+        type_1 => {
+            invalid_types(vec!(&type_1), "dedupe")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//sorts any values with total_order, equal elements keep their order
+pub fn sort(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    sort_list(list, stack, "sort", false)
+}
+
+pub fn sort_desc(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    sort_list(list, stack, "sort-desc", true)
+}
+
+//sorts by the values the lambda returns for the elements, the lambda is called once for every element
+pub fn sort_by(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    sort_list_by(list, stack, "sort-by", false)
+}
+
+pub fn sort_by_desc(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    sort_list_by(list, stack, "sort-by-desc", true)
+}
+
+//sorts with a comparator lambda, which returns a number [negative, zero or positive like 'compare'] or true if the first argument comes first
+#[allow(clippy::useless_format)]
+pub fn sort_with(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "sort-with")?;
+    match (op_1, op_2) {
+        (Value::Lambda(lambda), Value::List(list)) => {
+            let sorted = merge_sort(list.into_cells(), &mut |a, b| {
+                match lambda.eval_with_trace(vec!(a.clone(), b.clone()), stack, format!("sort-with"))? {
+                    Value::Boolean(true) => Ok(Ordering::Less),
+                    Value::Boolean(false) => Ok(Ordering::Greater),
+                    value => match Number::from_value(&value) {
+                        Some(n) => Ok(number::compare(&n, &Number::Integer(0)).unwrap_or(Ordering::Equal)),
+                        None => Err(Error::new_with_origin("sort-with", format!("expected the comparator to return a number or a boolean, found {}.", value.type_str())))
                     }
                 }
+            })?;
+            Ok(Value::List(List::from_cells(sorted)))
+        },
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2), "sort-with")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+fn sort_list(list: &List, stack: &mut Stack, fn_name: &'static str, descending: bool) -> Result<Value, Error> {
+    let op_1 = resolve_forced_argument(list, stack, fn_name)?;
+    match op_1 {
+        Value::List(list) => {
+            let sorted = merge_sort(list.into_cells(), &mut |a, b| Ok(ordered(total_order(a, b), descending)))?;
+            Ok(Value::List(List::from_cells(sorted)))
+        },
+        type_1 => {
+            invalid_types(vec!(&type_1), fn_name)?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+fn sort_list_by(list: &List, stack: &mut Stack, fn_name: &'static str, descending: bool) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, fn_name)?;
+    match (op_1, op_2) {
+        (Value::Lambda(lambda), Value::List(list)) => {
+            let mut keyed = Vec::with_capacity(list.cells().len());
+            for value in list.into_cells() {
+                let key = lambda.eval_with_trace(vec!(value.clone()), stack, fn_name.to_owned())?;
+                keyed.push((key, value));
             }
-            let new_cells = sorted.into_iter()
-                .map(|x| Value::Integer(x))
-                .collect::<Vec<Value>>();
-            return Ok(Value::List(List::from_cells(new_cells)));
+            let sorted = merge_sort(keyed, &mut |a, b| Ok(ordered(total_order(&a.0, &b.0), descending)))?;
+            Ok(Value::List(List::from_cells(sorted.into_iter().map(|x| x.1).collect())))
         },
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2), fn_name)?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+fn ordered(ordering: Ordering, descending: bool) -> Ordering {
+    match descending {
+        true => ordering.reverse(),
+        false => ordering
+    }
+}
+
+//a stable merge sort whose comparison can fail, an element of the right half is only put before
+//an element of the left half if it is smaller
+fn merge_sort<T, F>(mut values: Vec<T>, compare: &mut F) -> Result<Vec<T>, Error> where F: FnMut(&T, &T) -> Result<Ordering, Error> {
+    if values.len() <= 1 {
+        return Ok(values);
+    }
+    let right = values.split_off(values.len() / 2);
+    let left = merge_sort(values, compare)?;
+    let right = merge_sort(right, compare)?;
+    let mut result = Vec::with_capacity(left.len() + right.len());
+    let mut left_iter = left.into_iter().peekable();
+    let mut right_iter = right.into_iter().peekable();
+    loop {
+        let take_right = match (left_iter.peek(), right_iter.peek()) {
+            (Some(l), Some(r)) => compare(r, l)? == Ordering::Less,
+            (Some(_), None) => false,
+            (None, Some(_)) => true,
+            (None, None) => break
+        };
+        match take_right {
+            true => result.push(right_iter.next().unwrap()),
+            false => result.push(left_iter.next().unwrap())
+        }
+    }
+    Ok(result)
+}
+
+//calls the lambda with the elements at the same index of all lists, until the shortest list ends
+#[allow(clippy::useless_format)]
+fn map_lists(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let mut values = resolve_forced_arguments(list, stack, "map")?;
+    let lambda = match values.remove(0) {
+        Value::Lambda(lambda) => lambda,
         type_1 => {
-            invalid_types(vec!(&type_1), "sort")?;
+            let mut types = vec!(&type_1);
+            types.extend(values.iter());
+            invalid_types(types, "map")?;
+            return Ok(Value::Nil);
+        }
+    };
+    let lists = into_lists(values, "map")?;
+    let len = lists.iter().map(|x| x.len()).min().unwrap();
+    let mut result = Vec::with_capacity(len);
+    for index in 0..len {
+        let args = lists.iter().map(|x| x[index].clone()).collect();
+        result.push(lambda.eval_with_trace(args, stack, format!("map"))?);
+    }
+    Ok(Value::List(List::from_cells(result)))
+}
+
+//the elements of the values, which have to be lists
+fn into_lists(values: Vec<Value>, fn_name: &'static str) -> Result<Vec<Vec<Value>>, Error> {
+    if values.iter().any(|x| !matches!(x, &Value::List(_))) {
+        invalid_types(values.iter().collect(), fn_name)?;
+    }
+    Ok(values.into_iter().map(|x| match x {
+        Value::List(list) => list.into_cells(),
+        _ => unreachable!()
+    }).collect())
+}
+
+//resolves a positive size and a list
+fn size_and_list(list: &List, stack: &mut Stack, fn_name: &'static str) -> Result<(usize, Vec<Value>), Error> {
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, fn_name)?;
+    match (op_1, op_2) {
+        (Value::Integer(size), Value::List(list)) => {
+            if size <= 0 {
+                return Err(Error::new_with_origin(fn_name, format!("the size has to be positive, found {}.", size)));
+            }
+            Ok((size as usize, list.into_cells()))
+        },
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2), fn_name)?;
+            Ok((0, Vec::new()))
+        }
+    }
+}
+
+//calls a lambda that has to return a boolean
+fn test(lambda: &Lambda, value: &Value, index: usize, stack: &mut Stack, fn_name: &'static str) -> Result<bool, Error> {
+    match lambda.eval_with_trace(vec!(value.clone()), stack, fn_name.to_owned())? {
+        Value::Boolean(boolean) => Ok(boolean),
+        _ => Err(Error::new_with_origin(fn_name, format!("expected boolean at index {}.", index)))
+    }
+}
+
+//the first element whose key is ordered before [or after] the keys of all other elements
+#[allow(clippy::useless_format)]
+fn extremum_by(list: &List, stack: &mut Stack, fn_name: &'static str, wanted: Ordering) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, fn_name)?;
+    match (op_1, op_2) {
+        (Value::Lambda(lambda), Value::List(list)) => {
+            let mut best: Option<(Value, Value)> = None;
+            for elem in list.into_cells() {
+                let key = lambda.eval_with_trace(vec!(elem.clone()), stack, fn_name.to_owned())?;
+                let is_better = match best {
+                    Some((ref best_key, _)) => total_order(&key, best_key) == wanted,
+                    None => true
+                };
+                if is_better {
+                    best = Some((key, elem));
+                }
+            }
+            match best {
+                Some((_, elem)) => Ok(elem),
+                None => Err(Error::new_with_origin(fn_name, format!("the list is empty.")))
+            }
+        },
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2), fn_name)?;
+            Ok(Value::Nil)
         }
     }
-    Ok(Value::Nil)
 }
 
-//TODO: add function 'shape' which gives the nested size of a nested list like apls shape
 //TODO: add function 'split'
\ No newline at end of file
@@ -0,0 +1,125 @@
+use list::List;
+use error::Error;
+use functions::assert_min_length;
+use functions::invalid_types;
+use functions::resolve_argument;
+use functions::resolve_arguments;
+use functions::resolve_two_arguments;
+use map::Map;
+use value::Value;
+use stack::Stack;
+
+//creates a map from alternating keys and values: (dict :a 1 :b 2)
+pub fn dict(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let args = resolve_arguments(list, stack, "dict")?;
+    let mut map = Map::new();
+    insert_pairs(&mut map, args, "dict")?;
+    Ok(Value::Map(map))
+}
+
+//the value of the key, the optional third argument is returned if the key is missing
+pub fn get(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 2, "get")?;
+    let mut args = resolve_arguments(list, stack, "get")?;
+    if args.len() > 3 {
+        return Err(Error::new_with_origin("get", format!("expected 2 or 3 parameters, found {}.", args.len())));
+    }
+    let default = match args.len() {
+        3 => args.pop(),
+        _ => None
+    };
+    let key = args.pop().unwrap();
+    match args.pop().unwrap() {
+        Value::Map(map) => match (map.get(&key), default) {
+            (Some(value), _) => Ok(value.clone()),
+            (None, Some(default)) => Ok(default),
+            (None, None) => Err(Error::new_with_origin("get", format!("the map does not contain the key {}.", key)))
+        },
+        type_1 => {
+            invalid_types(vec!(&type_1), "get")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//a copy of the map with the given keys set to the given values
+pub fn assoc(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 1, "assoc")?;
+    let mut args = resolve_arguments(list, stack, "assoc")?;
+    let pairs = args.split_off(1);
+    match args.pop().unwrap() {
+        Value::Map(mut map) => {
+            insert_pairs(&mut map, pairs, "assoc")?;
+            Ok(Value::Map(map))
+        },
+        type_1 => {
+            invalid_types(vec!(&type_1), "assoc")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//a copy of the map without the given keys
+pub fn dissoc(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 1, "dissoc")?;
+    let mut args = resolve_arguments(list, stack, "dissoc")?;
+    let keys = args.split_off(1);
+    match args.pop().unwrap() {
+        Value::Map(mut map) => {
+            for key in &keys {
+                map.remove(key);
+            }
+            Ok(Value::Map(map))
+        },
+        type_1 => {
+            invalid_types(vec!(&type_1), "dissoc")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+pub fn has_key(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_arguments(list, stack, "has-key")?;
+    match op_1 {
+        Value::Map(map) => Ok(Value::Boolean(map.get(&op_2).is_some())),
+        type_1 => {
+            invalid_types(vec!(&type_1, &op_2), "has-key")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//the keys in the order they were added
+pub fn keys(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let op_1 = resolve_argument(list, stack, "keys")?;
+    match op_1 {
+        Value::Map(map) => Ok(Value::List(List::from_cells(map.entries().iter().map(|x| x.0.clone()).collect()))),
+        type_1 => {
+            invalid_types(vec!(&type_1), "keys")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//the values in the order their keys were added
+pub fn vals(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let op_1 = resolve_argument(list, stack, "vals")?;
+    match op_1 {
+        Value::Map(map) => Ok(Value::List(List::from_cells(map.entries().iter().map(|x| x.1.clone()).collect()))),
+        type_1 => {
+            invalid_types(vec!(&type_1), "vals")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+fn insert_pairs(map: &mut Map, args: Vec<Value>, fn_name: &'static str) -> Result<(), Error> {
+    if !args.len().is_multiple_of(2) {
+        return Err(Error::new_with_origin(fn_name, format!("expected a value for every key, found {} parameters.", args.len())));
+    }
+    let mut arg_iter = args.into_iter();
+    while let (Some(key), Some(value)) = (arg_iter.next(), arg_iter.next()) {
+        map.insert(key, value);
+    }
+    Ok(())
+}
\ No newline at end of file
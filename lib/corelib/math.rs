@@ -1,158 +1,367 @@
-use ::FLOAT;
 use list::List;
+use list::resolve;
 use error::Error;
 use functions::{
+    assert_min_length,
     resolve_argument,
+    resolve_arguments,
+    resolve_forced_arguments,
     resolve_two_arguments,
+    resolve_two_forced_arguments,
     invalid_types,
     to_float
 };
 use value::Value;
+use corelib::array::broadcast;
+use number;
+use number::Number;
+use number::big_to_value;
+use number::ratio_to_value;
+use number::Rounding;
 use stack::Stack;
+use ::FLOAT;
+
+use std::cmp::Ordering;
+use std::f64::consts;
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{
+    One,
+    Signed,
+    ToPrimitive,
+    Zero
+};
+
+//the largest number of bits shl shifts by and exact powers can have, larger numbers would need gigabytes of memory
+pub const MAX_SHIFT: usize = 1 << 24;
 
 pub fn add(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2) = resolve_two_arguments(list, stack, "add")?;
-    match (op_1, op_2) {
-        (Value::Integer(i_1), Value::Integer(i_2)) => {
-            return Ok(Value::Integer(i_1 + i_2));
-        },
-        (Value::Integer(i_1), Value::Float(f_2)) => {
-            return Ok(Value::Float(i_1 as FLOAT + f_2));
-        },
-        (Value::Float(f_1), Value::Integer(i_2)) => {
-            return Ok(Value::Float(f_1 + i_2 as FLOAT));
-        },
-        (Value::Float(f_1), Value::Float(f_2)) => {
-            return Ok(Value::Float(f_1 + f_2));
-        },
-        (type_1, type_2) => {
-            invalid_types(vec!(&type_1, &type_2), "add")?;
+    let values = resolve_forced_arguments(list, stack, "add")?;
+    broadcast(values, Value::Integer(0), "add", |x, y| Ok(number::add(x, y)))
+}
+
+//a single argument is negated
+pub fn sub(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 1, "sub")?;
+    let values = resolve_forced_arguments(list, stack, "sub")?;
+    broadcast(values, Value::Integer(0), "sub", |x, y| Ok(number::sub(x, y)))
+}
+
+pub fn mul(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let values = resolve_forced_arguments(list, stack, "mul")?;
+    broadcast(values, Value::Integer(1), "mul", |x, y| Ok(number::mul(x, y)))
+}
+
+//a single argument results in its reciprocal
+#[allow(clippy::useless_format)]
+pub fn div(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 1, "div")?;
+    let values = resolve_forced_arguments(list, stack, "div")?;
+    broadcast(values, Value::Integer(1), "div", |x, y| match number::div(x, y) {
+        Some(quotient) => Ok(quotient),
+        None => Err(Error::new_with_origin("div", format!("division by zero.")))
+    })
+}
+
+pub fn numerator(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let op_1 = resolve_argument(list, stack, "numerator")?;
+    match op_1 {
+        Value::Integer(_) | Value::BigInt(_) => Ok(op_1),
+        Value::Ratio(ratio) => Ok(big_to_value(ratio.numer().clone())),
+        type_1 => {
+            invalid_types(vec!(&type_1), "numerator")?;
+            Ok(Value::Nil)
         }
     }
-    Ok(Value::Nil)
 }
 
-pub fn sub(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2) = resolve_two_arguments(list, stack, "sub")?;
-    match (op_1, op_2) {
-        (Value::Integer(i_1), Value::Integer(i_2)) => {
-            return Ok(Value::Integer(i_1 - i_2));
-        },
-        (Value::Integer(i_1), Value::Float(f_2)) => {
-            return Ok(Value::Float(i_1 as FLOAT - f_2));
-        },
-        (Value::Float(f_1), Value::Integer(i_2)) => {
-            return Ok(Value::Float(f_1 - i_2 as FLOAT));
-        },
-        (Value::Float(f_1), Value::Float(f_2)) => {
-            return Ok(Value::Float(f_1 - f_2));
-        },
-        (type_1, type_2) => {
-            invalid_types(vec!(&type_1, &type_2), "sub")?;
+pub fn denominator(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let op_1 = resolve_argument(list, stack, "denominator")?;
+    match op_1 {
+        Value::Integer(_) | Value::BigInt(_) => Ok(Value::Integer(1)),
+        Value::Ratio(ratio) => Ok(big_to_value(ratio.denom().clone())),
+        type_1 => {
+            invalid_types(vec!(&type_1), "denominator")?;
+            Ok(Value::Nil)
         }
     }
-    Ok(Value::Nil)
 }
 
-pub fn mul(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2) = resolve_two_arguments(list, stack, "mul")?;
-    match (op_1, op_2) {
-        (Value::Integer(i_1), Value::Integer(i_2)) => {
-            return Ok(Value::Integer(i_1 * i_2));
-        },
-        (Value::Integer(i_1), Value::Float(f_2)) => {
-            return Ok(Value::Float(i_1 as FLOAT * f_2));
-        },
-        (Value::Float(f_1), Value::Integer(i_2)) => {
-            return Ok(Value::Float(f_1 * i_2 as FLOAT));
-        },
-        (Value::Float(f_1), Value::Float(f_2)) => {
-            return Ok(Value::Float(f_1 * f_2));
-        },
-        (type_1, type_2) => {
-            invalid_types(vec!(&type_1, &type_2), "mul")?;
+pub fn exact_to_inexact(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let op_1 = resolve_argument(list, stack, "exact->inexact")?;
+    match to_float(op_1.clone()) {
+        Some(f) => Ok(Value::Float(f)),
+        None => {
+            invalid_types(vec!(&op_1), "exact->inexact")?;
+            Ok(Value::Nil)
         }
     }
-    Ok(Value::Nil)
 }
 
-pub fn div(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2) = resolve_two_arguments(list, stack, "div")?;
-    let val_1 = to_float(op_1);
-    let val_2 = to_float(op_2);
-    match (val_1, val_2) {
-        (Some(v1), Some(v2)) => {
-            return Ok(Value::Float(v1 / v2));
-        },
-        _ => {
-            invalid_types(vec!(list.cells().get(1).unwrap(), list.cells().get(2).unwrap()), "div")?;
+//the simplest ratio that is rounded to a float, like 1/10 for 0.1, exact numbers are returned as they are
+//with a tolerance the simplest ratio that differs by at most the tolerance
+pub fn rationalize(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 1, "rationalize")?;
+    let op_1 = resolve(list.cells().get(1).unwrap().clone(), stack, "rationalize")?;
+    let number = match Number::from_value(&op_1) {
+        Some(number) => number,
+        None => {
+            invalid_types(vec!(&op_1), "rationalize")?;
+            return Ok(Value::Nil);
         }
+    };
+    let value = match (list.cells().len() < 3, &number) {
+        (true, &Number::Float(float)) => number::simplest_for_float(float),
+        _ => number.to_ratio()
+    };
+    let value = match value {
+        Some(ratio) => ratio,
+        None => return Err(Error::new_with_origin("rationalize", format!("{} has no exact value.", op_1)))
+    };
+    if list.cells().len() < 3 {
+        return Ok(ratio_to_value(value));
     }
-    Ok(Value::Nil)
+    let op_2 = resolve(list.cells().get(2).unwrap().clone(), stack, "rationalize")?;
+    let tolerance = match Number::from_value(&op_2).map(|x| x.to_ratio()) {
+        Some(Some(ratio)) => ratio.abs(),
+        Some(None) => return Err(Error::new_with_origin("rationalize", format!("{} is not a valid tolerance.", op_2))),
+        None => {
+            invalid_types(vec!(&op_2), "rationalize")?;
+            return Ok(Value::Nil);
+        }
+    };
+    Ok(ratio_to_value(number::simplest_between(&(&value - &tolerance), &(&value + &tolerance))))
 }
 
 pub fn sin(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let op_1 = resolve_argument(list, stack, "sin")?;
-    let val_1 = to_float(op_1);
-    match val_1 {
-        Some(f) => {
-            return Ok(Value::Float(f.sin()));
-        },
+    float_fn(list, stack, "sin", FLOAT::sin)
+}
+
+pub fn cos(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    float_fn(list, stack, "cos", FLOAT::cos)
+}
+
+pub fn tan(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    float_fn(list, stack, "tan", FLOAT::tan)
+}
+
+pub fn asin(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    float_fn(list, stack, "asin", FLOAT::asin)
+}
+
+pub fn acos(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    float_fn(list, stack, "acos", FLOAT::acos)
+}
+
+pub fn atan(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    float_fn(list, stack, "atan", FLOAT::atan)
+}
+
+pub fn sinh(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    float_fn(list, stack, "sinh", FLOAT::sinh)
+}
+
+pub fn cosh(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    float_fn(list, stack, "cosh", FLOAT::cosh)
+}
+
+pub fn tanh(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    float_fn(list, stack, "tanh", FLOAT::tanh)
+}
+
+pub fn asinh(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    float_fn(list, stack, "asinh", FLOAT::asinh)
+}
+
+pub fn acosh(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    float_fn(list, stack, "acosh", FLOAT::acosh)
+}
+
+pub fn atanh(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    float_fn(list, stack, "atanh", FLOAT::atanh)
+}
+
+//the angle of the point [x y], the first argument is y
+pub fn atan2(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_arguments(list, stack, "atan2")?;
+    match (to_float(op_1.clone()), to_float(op_2.clone())) {
+        (Some(y), Some(x)) => Ok(Value::Float(y.atan2(x))),
         _ => {
-            invalid_types(vec!(list.cells().get(1).unwrap()), "sin")?;
+            invalid_types(vec!(&op_1, &op_2), "atan2")?;
+            Ok(Value::Nil)
         }
     }
-    Ok(Value::Nil)
 }
 
-pub fn cos(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let op_1 = resolve_argument(list, stack, "cos")?;    
-    let val_1 = to_float(op_1);
-    match val_1 {
-        Some(f) => {
-            return Ok(Value::Float(f.cos()));
-        },
+pub fn exp(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    float_fn(list, stack, "exp", FLOAT::exp)
+}
+
+//the natural logarithm, or the logarithm to the base given as second argument
+pub fn log(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 1, "log")?;
+    if list.cells().len() == 2 {
+        return float_fn(list, stack, "log", FLOAT::ln);
+    }
+    let (op_1, op_2) = resolve_two_arguments(list, stack, "log")?;
+    match (to_float(op_1.clone()), to_float(op_2.clone())) {
+        (Some(x), Some(base)) => Ok(Value::Float(x.log(base))),
         _ => {
-            invalid_types(vec!(list.cells().get(1).unwrap()), "cos")?;
+            invalid_types(vec!(&op_1, &op_2), "log")?;
+            Ok(Value::Nil)
         }
     }
-    Ok(Value::Nil)
 }
 
-pub fn tan(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let op_1 = resolve_argument(list, stack, "tan")?;    
-    let val_1 = to_float(op_1);
-    match val_1 {
-        Some(f) => {
-            return Ok(Value::Float(f.tan()));
-        },
-        _ => {
-            invalid_types(vec!(list.cells().get(1).unwrap()), "tan")?;
+pub fn log2(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    float_fn(list, stack, "log2", FLOAT::log2)
+}
+
+pub fn log10(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    float_fn(list, stack, "log10", FLOAT::log10)
+}
+
+#[allow(clippy::useless_format)]
+//exact powers are limited to as many bits as shl can shift by
+pub fn pow(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "pow")?;
+    broadcast(vec!(op_1, op_2), Value::Nil, "pow", |x, y| {
+        let bits = number::power_bits(&x, &y);
+        if bits > MAX_SHIFT as u64 {
+            return Err(Error::new_with_origin("pow", format!("the exact result would have more than {} bits, found about {}.", MAX_SHIFT, bits)));
+        }
+        match number::pow(x, y) {
+            Some(result) => Ok(result),
+            None => Err(Error::new_with_origin("pow", format!("division by zero.")))
         }
+    })
+}
+
+pub fn sqrt(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    unary_number_fn(list, stack, "sqrt", |x| number::root(x, 2))
+}
+
+pub fn cbrt(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    unary_number_fn(list, stack, "cbrt", |x| number::root(x, 3))
+}
+
+pub fn abs(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    unary_number_fn(list, stack, "abs", number::abs)
+}
+
+pub fn floor(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    unary_number_fn(list, stack, "floor", |x| number::round(x, Rounding::Floor))
+}
+
+pub fn ceil(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    unary_number_fn(list, stack, "ceil", |x| number::round(x, Rounding::Ceil))
+}
+
+pub fn round(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    unary_number_fn(list, stack, "round", |x| number::round(x, Rounding::Round))
+}
+
+pub fn trunc(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    unary_number_fn(list, stack, "trunc", |x| number::round(x, Rounding::Trunc))
+}
+
+pub fn min(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    extremum(list, stack, "min", Ordering::Less)
+}
+
+pub fn max(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    extremum(list, stack, "max", Ordering::Greater)
+}
+
+//the quotient of an integer division, rounded towards zero
+#[allow(clippy::useless_format)]
+pub fn quot(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (i_1, i_2) = two_integers(list, stack, "quot")?;
+    if i_2.is_zero() {
+        return Err(Error::new_with_origin("quot", format!("division by zero.")));
     }
-    Ok(Value::Nil)
+    Ok(big_to_value(i_1 / i_2))
 }
 
-pub fn modulo(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    let (op_1, op_2) = resolve_two_arguments(list, stack, "mod")?;
-    match (op_1, op_2) {
-        (Value::Integer(i_1), Value::Integer(i_2)) => {
-            return Ok(Value::Integer(i_1 % i_2));
-        },
-        (Value::Integer(i_1), Value::Float(f_2)) => {
-            return Ok(Value::Float(i_1 as FLOAT % f_2));
-        },
-        (Value::Float(f_1), Value::Integer(i_2)) => {
-            return Ok(Value::Float(f_1 % i_2 as FLOAT));
-        },
-        (Value::Float(f_1), Value::Float(f_2)) => {
-            return Ok(Value::Float(f_1 % f_2));
-        },
-        (type_1, type_2) => {
-            invalid_types(vec!(&type_1, &type_2), "mod")?;
+//the remainder of an integer division, it has the sign of the dividend
+#[allow(clippy::useless_format)]
+pub fn rem(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (i_1, i_2) = two_integers(list, stack, "rem")?;
+    if i_2.is_zero() {
+        return Err(Error::new_with_origin("rem", format!("division by zero.")));
+    }
+    Ok(big_to_value(i_1 % i_2))
+}
+
+pub fn gcd(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let integers = integers(list, stack, "gcd")?;
+    Ok(big_to_value(integers.iter().fold(BigInt::zero(), |acc, x| acc.gcd(x))))
+}
+
+pub fn lcm(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let integers = integers(list, stack, "lcm")?;
+    Ok(big_to_value(integers.iter().fold(BigInt::one(), |acc, x| acc.lcm(x))))
+}
+
+pub fn bit_and(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let integers = integers(list, stack, "bit-and")?;
+    Ok(big_to_value(integers.iter().fold(-BigInt::one(), |acc, x| acc & x)))
+}
+
+pub fn bit_or(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let integers = integers(list, stack, "bit-or")?;
+    Ok(big_to_value(integers.iter().fold(BigInt::zero(), |acc, x| acc | x)))
+}
+
+pub fn bit_xor(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let integers = integers(list, stack, "bit-xor")?;
+    Ok(big_to_value(integers.iter().fold(BigInt::zero(), |acc, x| acc ^ x)))
+}
+
+pub fn bit_not(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let op_1 = resolve_argument(list, stack, "bit-not")?;
+    match op_1 {
+        Value::Integer(i) => Ok(Value::Integer(!i)),
+        Value::BigInt(b) => Ok(big_to_value(!b)),
+        type_1 => {
+            invalid_types(vec!(&type_1), "bit-not")?;
+            Ok(Value::Nil)
         }
     }
-    Ok(Value::Nil)
+}
+
+pub fn shl(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (value, shift) = shift_arguments(list, stack, "shl")?;
+    if shift > MAX_SHIFT && !value.is_zero() {
+        return Err(Error::new_with_origin("shl", format!("the shift amount can be at most {}, found {}.", MAX_SHIFT, shift)));
+    }
+    Ok(big_to_value(value << shift))
+}
+
+//an arithmetic shift, negative numbers stay negative
+pub fn shr(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (value, shift) = shift_arguments(list, stack, "shr")?;
+    Ok(big_to_value(value >> shift))
+}
+
+//the values of the constants pi, e, inf and nan, they are set in the global scope of a new interpreter
+pub fn constants() -> Vec<(&'static str, Value)> {
+    vec!(
+        ("pi", Value::Float(consts::PI)),
+        ("e", Value::Float(consts::E)),
+        ("inf", Value::Float(FLOAT::INFINITY)),
+        ("nan", Value::Float(FLOAT::NAN))
+    )
+}
+
+#[allow(clippy::useless_format)]
+pub fn modulo(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "mod")?;
+    broadcast(vec!(op_1, op_2), Value::Nil, "mod", |x, y| match number::rem(x, y) {
+        Some(result) => Ok(result),
+        None => Err(Error::new_with_origin("mod", format!("division by zero.")))
+    })
 }
 
 pub fn count(list: &List, stack: &mut Stack) -> Result<Value, Error> {
@@ -173,4 +382,82 @@ pub fn count(list: &List, stack: &mut Stack) -> Result<Value, Error> {
         }
     }
     Ok(Value::Nil)
+}
+
+//resolves one number and applies a float function to it
+fn float_fn(list: &List, stack: &mut Stack, name: &'static str, op: fn(FLOAT) -> FLOAT) -> Result<Value, Error> {
+    let op_1 = resolve_argument(list, stack, name)?;
+    match to_float(op_1.clone()) {
+        Some(f) => Ok(Value::Float(op(f))),
+        None => {
+            invalid_types(vec!(&op_1), name)?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//resolves one number and applies an operation that keeps exact numbers exact
+fn unary_number_fn(list: &List, stack: &mut Stack, name: &'static str, op: fn(Number) -> Number) -> Result<Value, Error> {
+    let op_1 = resolve_argument(list, stack, name)?;
+    match Number::from_value(&op_1) {
+        Some(n) => Ok(op(n).into_value()),
+        None => {
+            invalid_types(vec!(&op_1), name)?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//the first of the arguments for which no other argument has the given ordering to it
+fn extremum(list: &List, stack: &mut Stack, name: &'static str, ordering: Ordering) -> Result<Value, Error> {
+    assert_min_length(list, 1, name)?;
+    let mut result: Option<(Value, Number)> = None;
+    for value in resolve_arguments(list, stack, name)? {
+        let n = match Number::from_value(&value) {
+            Some(n) => n,
+            None => {
+                invalid_types(vec!(&value), name)?;
+                return Ok(Value::Nil);
+            }
+        };
+        let replace = match result {
+            None => true,
+            Some((_, ref best)) => number::compare(&n, best) == Some(ordering)
+        };
+        if replace {
+            result = Some((value, n));
+        }
+    }
+    Ok(result.unwrap().0)
+}
+
+fn two_integers(list: &List, stack: &mut Stack, name: &'static str) -> Result<(BigInt, BigInt), Error> {
+    let (op_1, op_2) = resolve_two_arguments(list, stack, name)?;
+    match (Number::from_value(&op_1).and_then(|x| x.to_integer()), Number::from_value(&op_2).and_then(|x| x.to_integer())) {
+        (Some(i_1), Some(i_2)) => Ok((i_1, i_2)),
+        _ => {
+            invalid_types(vec!(&op_1, &op_2), name)?;
+            unreachable!()
+        }
+    }
+}
+
+//resolves any number of integer arguments
+fn integers(list: &List, stack: &mut Stack, name: &'static str) -> Result<Vec<BigInt>, Error> {
+    let mut result = Vec::new();
+    for value in resolve_arguments(list, stack, name)? {
+        match Number::from_value(&value).and_then(|x| x.to_integer()) {
+            Some(integer) => result.push(integer),
+            None => invalid_types(vec!(&value), name)?
+        }
+    }
+    Ok(result)
+}
+
+fn shift_arguments(list: &List, stack: &mut Stack, name: &'static str) -> Result<(BigInt, usize), Error> {
+    let (value, shift) = two_integers(list, stack, name)?;
+    match shift.to_usize() {
+        Some(shift) => Ok((value, shift)),
+        None => Err(Error::new_with_origin(name, format!("the shift amount has to be a non-negative integer, found {}.", shift)))
+    }
 }
\ No newline at end of file
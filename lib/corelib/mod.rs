@@ -1,4 +1,13 @@
+pub mod array;
+pub mod atoms;
+pub mod binding;
 pub mod listops;
+pub mod maps;
 pub mod math;
+pub mod parallel;
 pub mod program;
-pub mod comp;
\ No newline at end of file
+pub mod records;
+pub mod seqs;
+pub mod symbols;
+pub mod comp;
+pub mod testing;
\ No newline at end of file
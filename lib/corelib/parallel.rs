@@ -0,0 +1,162 @@
+use list::List;
+use error::Error;
+use functions::assert_length;
+use functions::assert_min_length;
+use functions::invalid_types;
+use functions::resolve_argument;
+use functions::resolve_arguments;
+use functions::resolve_two_arguments;
+use functions::resolve_two_forced_arguments;
+use future::Future;
+use pool;
+use scope::Scope;
+use stack::Stack;
+use value::Value;
+use ::INT;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+//the number of jobs pmap creates for every thread of the pool, so that threads that finish early take over work
+const JOBS_PER_THREAD: usize = 4;
+
+//maps the lambda over the list on the thread pool, the results keep the order of the list
+//the lambda sees a snapshot of the variables that are visible at the call, the first error is thrown after all jobs finished
+#[allow(clippy::len_zero, clippy::useless_format)]
+pub fn pmap(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_forced_arguments(list, stack, "pmap")?;
+    let (lambda, values) = match (op_1, op_2) {
+        (Value::Lambda(lambda), Value::List(list)) => (lambda, list.into_cells()),
+        (Value::Lambda(_), Value::Nil) => return Ok(Value::List(List::empty())),
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2), "pmap")?;
+            return Ok(Value::Nil);
+        }
+    };
+    if values.len() == 0 {
+        return Ok(Value::List(List::empty()));
+    }
+    let globals = Arc::new(stack.flatten());
+    let chunk_len = values.len().div_ceil(pool::size() * JOBS_PER_THREAD);
+    let futures = values.chunks(chunk_len).map(|chunk| {
+        let (lambda, chunk, globals) = (lambda.clone(), chunk.to_vec(), globals.clone());
+        Future::run(move || {
+            let mut stack = Stack::from_scopes(vec!((*globals).clone()));
+            let mut results = Vec::with_capacity(chunk.len());
+            for value in chunk {
+                results.push(lambda.eval_with_trace(vec!(value), &mut stack, format!("pmap"))?);
+            }
+            Ok(Value::List(List::from_cells(results)))
+        })
+    }).collect::<Vec<_>>();
+    let mut results = Vec::with_capacity(values.len());
+    let mut error = None;
+    for future in futures {
+        match (future.wait(None).unwrap(), error.is_some()) {
+            (Ok(Value::List(list)), false) => results.extend(list.into_cells()),
+            (Err(err), false) => error = Some(err.add_trace(format!("pmap"))),
+            _ => {}
+        }
+    }
+    match error {
+        Some(err) => Err(err),
+        None => Ok(Value::List(List::from_cells(results)))
+    }
+}
+
+//evaluates the quoted code on the thread pool and returns a future of its value
+pub fn future(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let code = match resolve_argument(list, stack, "future")? {
+        Value::List(code) => code,
+        other => return Err(Error::new_with_origin("future", format!("a future can only evaluate a list, found {}.", other.type_str())))
+    };
+    Ok(Value::Future(Future::spawn(code, Arc::new(stack.flatten()))))
+}
+
+//starts every quoted list on the thread pool and returns their futures without waiting
+pub fn spawn_async(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 1, "spawn-async")?;
+    let futures = spawn_futures(list, stack, "spawn-async")?;
+    Ok(Value::List(List::from_cells(futures.into_iter().map(Value::Future).collect())))
+}
+
+//the futures of the quoted lists, they share one snapshot of the visible variables
+pub fn spawn_futures(list: &List, stack: &mut Stack, fn_name: &'static str) -> Result<Vec<Future>, Error> {
+    let mut codes = Vec::new();
+    for value in resolve_arguments(list, stack, fn_name)? {
+        match value {
+            Value::List(code) => codes.push(code),
+            other => return Err(Error::new_with_origin(fn_name, format!("thread can only evaluate a list, found {}.", other.type_str())))
+        }
+    }
+    let globals: Arc<Scope> = Arc::new(stack.flatten());
+    Ok(codes.into_iter().map(|x| Future::spawn(x, globals.clone())).collect())
+}
+
+//the value of a future or promise, (await future ms default) returns the default if the value is not there after ms milliseconds
+#[allow(clippy::get_first, clippy::needless_borrowed_reference, clippy::useless_format)]
+pub fn await_fn(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let args = resolve_arguments(list, stack, "await")?;
+    let (future, timeout, default) = match (args.len(), args.get(0), args.get(1)) {
+        (1, Some(&Value::Future(ref future)), _) => (future, None, Value::Nil),
+        (3, Some(&Value::Future(ref future)), Some(&Value::Integer(ms))) if ms >= 0 => {
+            (future, Some(Duration::from_millis(ms as u64)), args[2].clone())
+        },
+        (1, _, _) | (3, _, _) => {
+            invalid_types(args.iter().collect(), "await")?;
+            return Ok(Value::Nil);
+        },
+        (len, _, _) => return Err(Error::new(format!("await: requires 1 or 3 parameters, found {}.", len)))
+    };
+    match future.wait(timeout) {
+        Some(Ok(value)) => Ok(value),
+        Some(Err(err)) => Err(err.add_trace(format!("await"))),
+        None => Ok(default)
+    }
+}
+
+//a future without a value, deliver gives it one
+pub fn promise(list: &List, _stack: &mut Stack) -> Result<Value, Error> {
+    assert_length(list, 0, "promise")?;
+    Ok(Value::Future(Future::new()))
+}
+
+//sets the value of a promise, returns false if it already had one
+pub fn deliver(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_arguments(list, stack, "deliver")?;
+    match op_1 {
+        Value::Future(future) => Ok(Value::Boolean(future.complete(Ok(op_2)))),
+        type_1 => {
+            invalid_types(vec!(&type_1, &op_2), "deliver")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//true if the future or promise has a value or an error
+pub fn realized(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    match resolve_argument(list, stack, "realized?")? {
+        Value::Future(future) => Ok(Value::Boolean(future.is_realized())),
+        type_1 => {
+            invalid_types(vec!(&type_1), "realized?")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//(pool-size) is the number of threads of the pool, (pool-size n) changes it
+#[allow(clippy::useless_format)]
+pub fn pool_size(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let args = resolve_arguments(list, stack, "pool-size")?;
+    match args.first() {
+        None => {},
+        Some(&Value::Integer(size)) if size > 0 && args.len() == 1 => {
+            pool::set_size(size as usize).map_err(|x| x.set_origin(format!("pool-size")))?;
+        },
+        Some(_) if args.len() == 1 => {
+            return Err(Error::new_with_origin("pool-size", format!("expected a positive integer, found {}.", args[0])));
+        },
+        Some(_) => return Err(Error::new(format!("pool-size: requires 0 or 1 parameters, found {}.", args.len())))
+    }
+    Ok(Value::Integer(pool::size() as INT))
+}
\ No newline at end of file
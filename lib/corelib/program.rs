@@ -6,28 +6,28 @@ use functions::assert_min_length;
 use functions::invalid_types;
 use functions::resolve_two_arguments;
 use functions::resolve_argument;
+use symbol::Symbol;
 use value::Value;
 use lambda::Lambda;
+use lambda::Params;
 use stack::Stack;
-use interpreter::Interpreter;
-use std::thread;
+use profiler::Profiler;
+use formatter::format_template;
+use optimizer;
+use gc;
+use corelib::parallel::spawn_futures;
+use map::Map;
+use ::INT;
 
 pub fn lambda(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     let (op_1, op_2) = resolve_two_arguments(list, stack, "lambda")?;
     match (op_1, op_2) {
         (Value::List(params), Value::List(value)) => {
-            let mut args = Vec::new();
-            for param in params.cells() {
-                match param {
-                    &Value::Symbol(ref param_str) => {
-                        args.push(param_str.clone());
-                    },
-                    _ => {
-                        return Err(Error::new_with_origin("lambda", format!("only symbols can be used as function parameters.")));
-                    }
-                }
-            }
-            let lambda = Lambda::new(args, value);
+            let params = match Params::from_cells(params.cells()) {
+                Ok(params) => params,
+                Err(err) => return Err(Error::new_with_origin("lambda", err.message().clone()))
+            };
+            let lambda = Lambda::new(params, value);
             return Ok(Value::new_lambda(lambda));
         },
         (type_1, type_2) => {
@@ -37,6 +37,24 @@ pub fn lambda(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     Ok(Value::Nil)
 }
 
+//binds the first arguments of a lambda, the result takes the remaining ones
+pub fn partial(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 1, "partial")?;
+    let function = resolve(list.cells().get(1).unwrap().clone(), stack, "partial")?;
+    let mut args = Vec::new();
+    for cell in list.cells().iter().skip(2) {
+        args.push(resolve(cell.clone(), stack, "partial")?);
+    }
+    match function {
+        Value::Lambda(lambda) => Ok(Value::Lambda(lambda.partial(args))),
+        type_1 => {
+            invalid_types(vec!(&type_1), "partial")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+#[allow(clippy::get_first, clippy::needless_question_mark, clippy::useless_format)]
 pub fn cond(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     assert_min_length(list, 1, "cond")?;
     for i in 1..list.cells().len() {
@@ -64,11 +82,12 @@ pub fn cond(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     Err(Error::new_with_origin("cond", format!("no condition was true.")))
 }
 
+#[allow(clippy::useless_format)]
 pub fn set(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     let (op_1, op_2) = resolve_two_arguments(list, stack, "set")?;
     match (op_1, op_2) {
         (type_1, Value::Symbol(_)) => {
-            invalid_types(vec!(&type_1, &Value::Symbol(String::new())), "set")?;
+            invalid_types(vec!(&type_1, &Value::Symbol(Symbol::new(""))), "set")?;
         },
         (Value::Symbol(name), value) => {
             if stack.size() <= 1 {
@@ -88,11 +107,12 @@ pub fn set(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     Ok(Value::Nil)
 }
 
+#[allow(clippy::useless_format)]
 pub fn global(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     let (op_1, op_2) = resolve_two_arguments(list, stack, "global")?;
     match (op_1, op_2) {
         (type_1, Value::Symbol(_)) => {
-            invalid_types(vec!(&type_1, &Value::Symbol(String::new())), "global")?;
+            invalid_types(vec!(&type_1, &Value::Symbol(Symbol::new(""))), "global")?;
         },
         (Value::Symbol(name), value) => {
             if stack.size() == 0 {
@@ -112,11 +132,35 @@ pub fn quote(list: &List, _stack: &mut Stack) -> Result<Value, Error> {
     Ok(list.cells().get(1).unwrap().clone())
 }
 
+#[allow(clippy::needless_question_mark)]
 pub fn eval(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     let op_1 = resolve_argument(list, stack, "eval")?;
     Ok(resolve(op_1, stack, "eval")?)
 }
 
+//collects the cycles that are not used anymore and returns the number of atoms and lists that were freed
+pub fn gc_fn(list: &List, _stack: &mut Stack) -> Result<Value, Error> {
+    assert_length(list, 0, "gc")?;
+    Ok(Value::Integer(gc::collect() as INT))
+}
+
+pub fn gc_stats(list: &List, _stack: &mut Stack) -> Result<Value, Error> {
+    assert_length(list, 0, "gc-stats")?;
+    let stats = gc::stats();
+    let mut map = Map::new();
+    for &(key, value) in &[(":collections", stats.collections), (":freed-atoms", stats.freed_atoms), (":freed-lists", stats.freed_lists),
+                           (":atoms", stats.atoms), (":candidates", stats.candidates)] {
+        map.insert(Value::Symbol(Symbol::new(key)), Value::Integer(value as INT));
+    }
+    Ok(Value::Map(map))
+}
+
+//the code as the optimizer rewrites it before it is evaluated
+pub fn optimize(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let op_1 = resolve_argument(list, stack, "optimize")?;
+    Ok(optimizer::optimize(&op_1))
+}
+
 pub fn printfmt(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     let op_1 = resolve_argument(list, stack, "printfmt")?;
     println!("{:?}", op_1);
@@ -129,6 +173,7 @@ pub fn print(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     Ok(Value::Nil)
 }
 
+#[allow(clippy::bool_comparison)]
 pub fn while_loop(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     let (op_1, op_2) = resolve_two_arguments(list, stack, "while")?;
     match (op_1, op_2) {
@@ -156,46 +201,22 @@ pub fn while_loop(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     Ok(Value::Nil)
 }
 
+//evaluates the quoted lists on the thread pool and returns their values once all of them finished
+//the lists see one snapshot of the visible variables, atoms in it are shared
 pub fn spawn(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     assert_min_length(list, 2, "spawn")?;
-    let mut handles = Vec::new();
-    for i in 1..list.cells().len() {
-        let maybe_listelem = resolve(list.cells().get(i).unwrap().clone(), stack, "spawn")?;
-        let listelem = match maybe_listelem {
-            Value::List(list) => list,
-            type1 => {
-                return Err(Error::new_with_origin("spawn", format!("thread can only evaluate a list, found {}.", type1.type_str())))
-            }
-        };
-        let handle = thread::spawn(move || {
-            let mut interpreter = Interpreter::new_empty();
-            interpreter.eval(listelem)
-        });
-        handles.push(handle);
-    }
+    let futures = spawn_futures(list, stack, "spawn")?;
     let mut retval = Vec::new();
-    let mut thread_counter = 0;
-    for elem in handles {
-        retval.push(
-            match elem.join() {
-                Ok(maybe_value) => {
-                    match maybe_value {
-                        Ok(value) => value,
-                        Err(err) => {
-                            return Err(err.add_trace(format!("thread {}", thread_counter)));
-                        }
-                    }
-                },
-                Err(_) => {
-                    return Err(Error::new_with_origin("spawn", format!("thread {} paniced.", thread_counter)));
-                }
-            }
-        );
-        thread_counter += 1;
+    for (thread_counter, future) in futures.into_iter().enumerate() {
+        match future.wait(None).unwrap() {
+            Ok(value) => retval.push(value),
+            Err(err) => return Err(err.add_trace(format!("thread {}", thread_counter)))
+        }
     }
     Ok(Value::List(List::from_cells(retval)))
 }
 
+#[allow(clippy::useless_format)]
 pub fn puts(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     let op_1 = resolve_argument(list, stack, "puts")?;
     match op_1 {
@@ -233,6 +254,7 @@ pub fn seq(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     Ok(retval)
 }
 
+#[allow(clippy::useless_format)]
 pub fn throw(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     let op_1 = resolve_argument(list, stack, "throw")?;
     if !op_1.is_list_and_string() {
@@ -260,6 +282,7 @@ pub fn throw(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     Ok(Value::Nil)
 }
 
+#[allow(clippy::needless_return)]
 pub fn try(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     assert_length(list, 2, "try")?;
     let op_1 = list.cells().get(1).unwrap().clone();
@@ -272,6 +295,7 @@ pub fn try(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     }
 }
 
+#[allow(clippy::useless_format)]
 pub fn try_rename(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     assert_length(list, 2, "try_rename")?;
     let op_1 = list.cells().get(1).unwrap().clone();
@@ -284,7 +308,7 @@ pub fn try_rename(list: &List, stack: &mut Stack) -> Result<Value, Error> {
                     match name {
                         Value::Symbol(symbol) => {
                             err.clear_trace();
-                            Err(err.set_origin(symbol))
+                            Err(err.set_origin(symbol.to_string()))
                         },
                         type_1 => {
                             Err(Error::new_with_origin("try_rename", format!("expected symbol as new trace root, found {}.", type_1.type_str())))
@@ -304,50 +328,71 @@ pub fn type_fn(list: &List, stack: &mut Stack) -> Result<Value, Error> {
     Ok(op_1.type_value())
 }
 
+#[allow(clippy::redundant_closure)]
 pub fn format(list: &List, stack: &mut Stack) -> Result<Value, Error> {
-    assert_min_length(list, 2, "format")?;
-    let mut cell_iter = list.clone().into_cells().into_iter().skip(1);
-    let mut string = String::new();
-    match resolve(cell_iter.next().unwrap(), stack, "format")? {
-        Value::List(inner_list) => {
-            for elem in inner_list.into_cells() {
-                match elem {
-                    Value::Char(ch) => string.push(ch),
-                    type_1 => {
-                        println!("{}", type_1);
-                        return Err(Error::new_with_origin("format", format!("a string only contains characters, found type {} in list.", type_1.type_str())))
-                    }
-                }
-            }
-        }
-        type_1 => {
-            println!("{}", type_1);
-            invalid_types(vec!(&type_1), "format")?;
-        }
+    assert_min_length(list, 1, "format")?;
+    let template = resolve(list.cells().get(1).unwrap().clone(), stack, "format")?;
+    if !template.is_list_and_string() {
+        invalid_types(vec!(&template), "format")?;
     }
+    let template = match template {
+        Value::List(template) => template.into_cells().into_iter().map(|x| format!("{}", x)).collect::<String>(),
+        _ => unreachable!()
+    };
     let mut args = Vec::new();
-    for cell in cell_iter {
-        args.push(resolve(cell, stack, "format")?);
+    for cell in list.cells().iter().skip(2) {
+        args.push(resolve(cell.clone(), stack, "format")?);
     }
-    let mut temp = Vec::new();
-    for split in string.split("$$") {
-        temp.push(split);
+    match format_template(&template, &args) {
+        Ok(string) => Ok(Value::List(List::from_cells(string.chars().map(|x| Value::Char(x)).collect()))),
+        Err(err) => Err(Error::new_with_origin("format", err.message().clone()))
     }
-    let mut result = Vec::new();
-    let templen = temp.len();
-    if templen-1 != args.len() {
-        return Err(Error::new_with_origin("format", format!("template and argument count does not match. template count: {}, arg count: {}", templen-1, args.len())))
-    }
-    let mut temp_iter = temp.into_iter();
-    for i in 0..templen-1 {
-        let mut current = temp_iter.next().unwrap().to_owned();
-        for ch in current.chars() {
-            result.push(Value::Char(ch));
+}
+
+pub fn break_fn(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_length(list, 0, "break")?;
+    stack.debug_break(list)?;
+    Ok(Value::Nil)
+}
+
+//evaluates the argument with a new profiler and prints its results
+pub fn profile(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_length(list, 1, "profile")?;
+    let outer = stack.take_profiler();
+    stack.set_profiler(Some(Profiler::new()));
+    let result = resolve(list.cells().get(1).unwrap().clone(), stack, "profile");
+    let profiler = stack.take_profiler().unwrap();
+    stack.set_profiler(outer);
+    print!("{}", profiler.table());
+    result
+}
+
+//adds functions to the names that are traced
+pub fn trace(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 1, "trace")?;
+    for i in 1..list.cells().len() {
+        match resolve(list.cells().get(i).unwrap().clone(), stack, "trace")? {
+            Value::Symbol(name) => stack.tracer_mut().trace(name.to_string()),
+            type_1 => {
+                return Err(Error::new_with_origin("trace", format!("expected a function name, found {}.", type_1.type_str())));
+            }
         }
-        current = format!("{}", args.get(i).unwrap());
-        for ch in current.chars() {
-            result.push(Value::Char(ch));
+    }
+    Ok(Value::Nil)
+}
+
+//removes functions from the names that are traced, without arguments nothing is traced anymore
+pub fn untrace(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    if list.cells().len() == 1 {
+        stack.tracer_mut().untrace_all();
+    }
+    for i in 1..list.cells().len() {
+        match resolve(list.cells().get(i).unwrap().clone(), stack, "untrace")? {
+            Value::Symbol(name) => stack.tracer_mut().untrace(name.to_string()),
+            type_1 => {
+                return Err(Error::new_with_origin("untrace", format!("expected a function name, found {}.", type_1.type_str())));
+            }
         }
     }
-    Ok(Value::List(List::from_cells(result)))
+    Ok(Value::Nil)
 }
\ No newline at end of file
@@ -0,0 +1,159 @@
+use list::List;
+use error::Error;
+use functions::assert_min_length;
+use functions::invalid_types;
+use functions::resolve_arguments;
+use functions::resolve_two_arguments;
+use lambda::Lambda;
+use lambda::Params;
+use record::Record;
+use symbol::Symbol;
+use value::Value;
+use stack::Stack;
+
+//defines the record type in the global scope:
+//the constructor (Name fields...), the predicate (Name? x), the accessors (Name-field x) and the updaters (Name-with-field x value)
+#[allow(clippy::needless_borrowed_reference, clippy::useless_format)]
+pub fn defrecord(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_arguments(list, stack, "defrecord")?;
+    let (name, fields) = match (op_1, op_2) {
+        (Value::Symbol(name), Value::List(fields)) => (name, fields),
+        (Value::Symbol(name), Value::Nil) => (name, List::empty()),
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2), "defrecord")?;
+            return Ok(Value::Nil);
+        }
+    };
+    let mut field_names = Vec::new();
+    for field in fields.cells() {
+        match field {
+            &Value::Symbol(ref field) if !field.starts_with(':') && !field.starts_with('&') && !field_names.contains(field) => {
+                field_names.push(field.clone());
+            },
+            other => {
+                return Err(Error::new_with_origin("defrecord", format!("expected unique symbols as field names, found {}.", other)));
+            }
+        }
+    }
+    let quoted_name = quote(Value::Symbol(name.clone()));
+    let mut definitions = Vec::new();
+    let mut constructor = vec!(symbol("make-record"), quoted_name.clone(), quote(Value::List(fields.clone())));
+    constructor.extend(field_names.iter().map(|x| symbol(x)));
+    definitions.push((name.clone(), define(&field_names, constructor)?));
+    definitions.push((Symbol::from(format!("{}?", name)), define(&[Symbol::new("x")], vec!(symbol("record?"), symbol("x"), quoted_name.clone()))?));
+    for field in &field_names {
+        let quoted_field = quote(Value::Symbol(field.clone()));
+        let accessor = vec!(symbol("record-get"), symbol("x"), quoted_field.clone(), quoted_name.clone());
+        definitions.push((Symbol::from(format!("{}-{}", name, field)), define(&[Symbol::new("x")], accessor)?));
+        let updater = vec!(symbol("record-with"), symbol("x"), quoted_field, symbol("value"), quoted_name.clone());
+        definitions.push((Symbol::from(format!("{}-with-{}", name, field)), define(&[Symbol::new("x"), Symbol::new("value")], updater)?));
+    }
+    if stack.size() == 0 {
+        return Err(Error::new_with_origin("defrecord", format!("no scope found.")));
+    }
+    let global = stack.get_mut_first().unwrap();
+    for (name, lambda) in definitions {
+        global.set_variable(name, lambda);
+    }
+    Ok(Value::Nil)
+}
+
+//creates a record from its name, the list of field names and a value for every field
+pub fn make_record(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 2, "make-record")?;
+    let mut args = resolve_arguments(list, stack, "make-record")?;
+    let values = args.split_off(2);
+    match (args.remove(0), args.remove(0)) {
+        (Value::Symbol(name), fields) => {
+            let fields = match fields {
+                Value::List(fields) => fields.into_cells(),
+                _ => Vec::new()
+            };
+            if fields.len() != values.len() {
+                return Err(Error::new_with_origin("make-record", format!("'{}': expected {} fields, found {}.", name, fields.len(), values.len())));
+            }
+            let mut entries = Vec::new();
+            for (field, value) in fields.into_iter().zip(values) {
+                match field {
+                    Value::Symbol(field) => entries.push((field, value)),
+                    other => return Err(Error::new_with_origin("make-record", format!("expected symbols as field names, found {}.", other)))
+                }
+            }
+            Ok(Value::Record(Record::new(name, entries)))
+        },
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2), "make-record")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//true if the value is a record, the optional second argument is the name of the record type
+#[allow(clippy::get_first, clippy::needless_borrowed_reference)]
+pub fn is_record(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 1, "record?")?;
+    let args = resolve_arguments(list, stack, "record?")?;
+    match (args.get(0).unwrap(), args.get(1), args.len()) {
+        (&Value::Record(_), None, 1) => Ok(Value::Boolean(true)),
+        (&Value::Record(ref record), Some(&Value::Symbol(ref name)), 2) => Ok(Value::Boolean(record.name() == name)),
+        (_, None, 1) | (_, Some(&Value::Symbol(_)), 2) => Ok(Value::Boolean(false)),
+        _ => {
+            invalid_types(args.iter().collect(), "record?")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//the value of a field, the optional third argument is the expected name of the record type
+pub fn record_get(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 2, "record-get")?;
+    let args = resolve_arguments(list, stack, "record-get")?;
+    let (record, field) = record_and_field(&args, 2, "record-get")?;
+    match record.get(field) {
+        Some(value) => Ok(value.clone()),
+        None => Err(Error::new_with_origin("record-get", format!("'{}' has no field '{}'.", record.name(), field)))
+    }
+}
+
+//a copy of the record with a new value for the field, the optional fourth argument is the expected name of the record type
+pub fn record_with(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 3, "record-with")?;
+    let args = resolve_arguments(list, stack, "record-with")?;
+    let (record, field) = record_and_field(&args, 3, "record-with")?;
+    match record.with(field, args.get(2).unwrap().clone()) {
+        Some(record) => Ok(Value::Record(record)),
+        None => Err(Error::new_with_origin("record-with", format!("'{}' has no field '{}'.", record.name(), field)))
+    }
+}
+
+//the record and the field name of the arguments, checks the type name if it is given after the other arguments
+#[allow(clippy::get_first, clippy::needless_borrowed_reference)]
+fn record_and_field<'a>(args: &'a [Value], type_index: usize, fn_name: &'static str) -> Result<(&'a Record, &'a Symbol), Error> {
+    if args.len() > type_index + 1 {
+        return Err(Error::new_with_origin(fn_name, format!("expected {} or {} parameters, found {}.", type_index, type_index + 1, args.len())));
+    }
+    match (args.get(0).unwrap(), args.get(1).unwrap(), args.get(type_index)) {
+        (&Value::Record(ref record), &Value::Symbol(ref field), None) => Ok((record, field)),
+        (&Value::Record(ref record), &Value::Symbol(ref field), Some(&Value::Symbol(ref name))) if record.name() == name => Ok((record, field)),
+        (other, &Value::Symbol(_), Some(&Value::Symbol(ref name))) => {
+            Err(Error::new_with_origin(fn_name, format!("expected a record of type '{}', found {}.", name, other.type_str())))
+        },
+        _ => {
+            invalid_types(args.iter().collect(), fn_name)?;
+            unreachable!()
+        }
+    }
+}
+
+fn define(params: &[Symbol], body: Vec<Value>) -> Result<Value, Error> {
+    let params = Params::from_cells(&params.iter().map(|x| symbol(x)).collect::<Vec<_>>())?;
+    Ok(Value::new_lambda(Lambda::new(params, List::from_cells(body))))
+}
+
+fn symbol(name: &str) -> Value {
+    Value::Symbol(Symbol::new(name))
+}
+
+fn quote(value: Value) -> Value {
+    Value::List(List::from_cells(vec!(symbol("quote"), value)))
+}
\ No newline at end of file
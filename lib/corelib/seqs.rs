@@ -0,0 +1,129 @@
+use list::List;
+use error::Error;
+use functions::force;
+use functions::invalid_types;
+use functions::resolve_argument;
+use functions::resolve_arguments;
+use functions::resolve_two_arguments;
+use lazy::LazySeq;
+use number::Number;
+use value::Value;
+use stack::Stack;
+
+//(range) counts from 0 infinitely, (range end), (range start end) and (range start end step) end before end
+#[allow(clippy::useless_format)]
+pub fn range(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let args = resolve_arguments(list, stack, "range")?;
+    if args.iter().any(|x| Number::from_value(x).is_none()) {
+        invalid_types(args.iter().collect(), "range")?;
+    }
+    let (start, end, step) = match args.len() {
+        0 => (Value::Integer(0), None, Value::Integer(1)),
+        1 => (Value::Integer(0), Some(args[0].clone()), Value::Integer(1)),
+        2 => (args[0].clone(), Some(args[1].clone()), Value::Integer(1)),
+        3 => (args[0].clone(), Some(args[1].clone()), args[2].clone()),
+        len => return Err(Error::new_with_origin("range", format!("expected 0 to 3 parameters, found {}.", len)))
+    };
+    if Number::from_value(&step).unwrap().is_zero() {
+        return Err(Error::new_with_origin("range", format!("the step can not be zero.")));
+    }
+    Ok(Value::LazySeq(LazySeq::range(start, end, step)))
+}
+
+//the infinite sequence x, (f x), (f (f x)), ...
+pub fn iterate(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_arguments(list, stack, "iterate")?;
+    match op_1 {
+        Value::Lambda(lambda) => Ok(Value::LazySeq(LazySeq::iterate(lambda, op_2))),
+        type_1 => {
+            invalid_types(vec!(&type_1, &op_2), "iterate")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//repeats the value infinitely or the number of times given as second argument
+pub fn repeat(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let mut args = resolve_arguments(list, stack, "repeat")?;
+    match args.len() {
+        1 => Ok(Value::LazySeq(LazySeq::repeat(args.remove(0), None))),
+        2 => match args[1] {
+            Value::Integer(times) if times >= 0 => Ok(Value::LazySeq(LazySeq::repeat(args.remove(0), Some(times as usize)))),
+            _ => {
+                invalid_types(args.iter().collect(), "repeat")?;
+                Ok(Value::Nil)
+            }
+        },
+        len => Err(Error::new_with_origin("repeat", format!("expected 1 or 2 parameters, found {}.", len)))
+    }
+}
+
+//repeats the elements of the list infinitely
+pub fn cycle(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let op_1 = resolve_argument(list, stack, "cycle")?;
+    match force(op_1, stack)? {
+        Value::List(list) => Ok(Value::LazySeq(LazySeq::cycle(list.into_cells()))),
+        type_1 => {
+            invalid_types(vec!(&type_1), "cycle")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//the first n elements, lazy for lazy sequences
+pub fn take(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_arguments(list, stack, "take")?;
+    match (op_1, op_2) {
+        (Value::Integer(count), Value::LazySeq(seq)) if count >= 0 => Ok(Value::LazySeq(seq.take(count as usize))),
+        (Value::Integer(count), Value::List(list)) if count >= 0 => {
+            Ok(Value::List(List::from_cells(list.into_cells().into_iter().take(count as usize).collect())))
+        },
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2), "take")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//all elements except for the first n, lazy for lazy sequences
+pub fn drop(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_arguments(list, stack, "drop")?;
+    match (op_1, op_2) {
+        (Value::Integer(count), Value::LazySeq(seq)) if count >= 0 => Ok(Value::LazySeq(seq.drop(count as usize))),
+        (Value::Integer(count), Value::List(list)) if count >= 0 => {
+            Ok(Value::List(List::from_cells(list.into_cells().into_iter().skip(count as usize).collect())))
+        },
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2), "drop")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//the elements before the first one for which the lambda returns false, lazy for lazy sequences
+pub fn take_while(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let (op_1, op_2) = resolve_two_arguments(list, stack, "take-while")?;
+    match (op_1, op_2) {
+        (Value::Lambda(lambda), Value::LazySeq(seq)) => Ok(Value::LazySeq(seq.take_while(lambda))),
+        (Value::Lambda(lambda), Value::List(list)) => {
+            let seq = LazySeq::from_values(list.into_cells()).take_while(lambda);
+            Ok(Value::List(List::from_cells(seq.force(stack)?)))
+        },
+        (type_1, type_2) => {
+            invalid_types(vec!(&type_1, &type_2), "take-while")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//computes all elements of a lazy sequence, lists stay unchanged
+pub fn into_list(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let op_1 = resolve_argument(list, stack, "into-list")?;
+    match force(op_1, stack)? {
+        Value::List(list) => Ok(Value::List(list)),
+        type_1 => {
+            invalid_types(vec!(&type_1), "into-list")?;
+            Ok(Value::Nil)
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,49 @@
+use list::List;
+use error::Error;
+use functions::invalid_types;
+use functions::resolve_argument;
+use functions::resolve_arguments;
+use symbol::Symbol;
+use value::Value;
+use stack::Stack;
+
+//a new symbol that is different from all symbols used so far, the prefix can be given as a symbol or a string
+#[allow(clippy::needless_borrowed_reference, clippy::useless_format)]
+pub fn gensym(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let args = resolve_arguments(list, stack, "gensym")?;
+    let prefix = match args.first() {
+        None => format!("G__"),
+        Some(&Value::Symbol(ref symbol)) if args.len() == 1 => symbol.to_string(),
+        Some(value) if args.len() == 1 && value.string_content().is_some() => value.string_content().unwrap(),
+        Some(_) if args.len() == 1 => {
+            invalid_types(args.iter().collect(), "gensym")?;
+            return Ok(Value::Nil);
+        },
+        Some(_) => return Err(Error::new_with_origin("gensym", format!("expected 0 or 1 parameters, found {}.", args.len())))
+    };
+    Ok(Value::Symbol(Symbol::gensym(&prefix)))
+}
+
+pub fn symbol_to_string(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let op_1 = resolve_argument(list, stack, "symbol->string")?;
+    match op_1 {
+        Value::Symbol(symbol) => Ok(Value::new_string(&symbol)),
+        type_1 => {
+            invalid_types(vec!(&type_1), "symbol->string")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+#[allow(clippy::useless_format)]
+pub fn string_to_symbol(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    let op_1 = resolve_argument(list, stack, "string->symbol")?;
+    match op_1.string_content() {
+        Some(ref string) if string.is_empty() => Err(Error::new_with_origin("string->symbol", format!("a symbol can not be empty."))),
+        Some(string) => Ok(Value::Symbol(Symbol::from(string))),
+        None => {
+            invalid_types(vec!(&op_1), "string->symbol")?;
+            Ok(Value::Nil)
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,163 @@
+use list::List;
+use list::resolve;
+use list::is_quote;
+use error::Error;
+use functions::assert_min_length;
+use functions::invalid_types;
+use value::Value;
+use stack::Stack;
+use testrunner::TestCase;
+
+//registers a test, the runner of 'ali test' evaluates its body later
+#[allow(clippy::redundant_field_names)]
+pub fn deftest(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 2, "deftest")?;
+    let name = match list.cells().get(1).unwrap().clone() {
+        Value::Symbol(symbol) => symbol.to_string(),
+        cell => {
+            let name_value = resolve(cell, stack, "deftest")?;
+            match name_value {
+                Value::Symbol(symbol) => symbol.to_string(),
+                other => match other.string_content() {
+                    Some(string) => string,
+                    None => {
+                        return Err(Error::new_with_origin("deftest", format!("expected a symbol or a string as test name, found {}.", other.type_str())));
+                    }
+                }
+            }
+        }
+    };
+    let mut body = Vec::new();
+    for i in 2..list.cells().len() {
+        body.push(unquote(list.cells().get(i).unwrap().clone()));
+    }
+    stack.add_test(TestCase {
+        name: name,
+        body: body
+    });
+    Ok(Value::Nil)
+}
+
+pub fn assert(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 1, "assert")?;
+    let expr = list.cells().get(1).unwrap().clone();
+    let value = resolve(expr.clone(), stack, "assert")?;
+    match value {
+        Value::Boolean(true) => Ok(Value::Nil),
+        Value::Boolean(false) => {
+            let mut message = format!("assertion failed: {}", expr);
+            if list.cells().len() > 2 {
+                let note = resolve(list.cells().get(2).unwrap().clone(), stack, "assert")?;
+                message.push_str(&format!(" ({})", note.string_content().unwrap_or(format!("{}", note))));
+            }
+            Err(Error::new_with_origin("assert", message))
+        },
+        type_1 => {
+            invalid_types(vec!(&type_1), "assert")?;
+            Ok(Value::Nil)
+        }
+    }
+}
+
+#[allow(clippy::single_match)]
+pub fn assert_eq(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 2, "assert-eq")?;
+    let expected = resolve(list.cells().get(1).unwrap().clone(), stack, "assert-eq")?;
+    let actual_expr = list.cells().get(2).unwrap().clone();
+    let actual = resolve(actual_expr.clone(), stack, "assert-eq")?;
+    if expected == actual {
+        return Ok(Value::Nil);
+    }
+    let mut message = format!("assertion failed: {}\n    expected: {:?}\n      actual: {:?}", actual_expr, expected, actual);
+    match (&expected, &actual) {
+        (&Value::List(_), &Value::List(_)) => {
+            match difference(&expected, &actual, String::new()) {
+                Some(diff) => message.push_str(&format!("\n  difference: {}", diff)),
+                None => {}
+            }
+        },
+        _ => {}
+    }
+    Err(Error::new_with_origin("assert-eq", message))
+}
+
+//succeeds if the evaluation of the first argument fails, the optional second argument has to be part of the error message
+#[allow(clippy::useless_format)]
+pub fn assert_throws(list: &List, stack: &mut Stack) -> Result<Value, Error> {
+    assert_min_length(list, 1, "assert-throws")?;
+    let expr = list.cells().get(1).unwrap().clone();
+    let size = stack.size();
+    let result = resolve(expr.clone(), stack, "assert-throws");
+    stack.truncate(size);
+    match result {
+        Ok(value) => {
+            Err(Error::new_with_origin("assert-throws", format!("expected an error from {}, found the value {:?}.", expr, value)))
+        },
+        Err(err) => {
+            if list.cells().len() > 2 {
+                let expected = resolve(list.cells().get(2).unwrap().clone(), stack, "assert-throws")?;
+                let expected_str = match expected.string_content() {
+                    Some(string) => string,
+                    None => {
+                        return Err(Error::new_with_origin("assert-throws", format!("the expected error message has to be a string.")));
+                    }
+                };
+                if !err.message().contains(&expected_str) {
+                    return Err(Error::new_with_origin("assert-throws", format!("expected an error containing \"{}\", found \"{}\".", expected_str, err.message())));
+                }
+            }
+            Ok(Value::Nil)
+        }
+    }
+}
+
+//removes the quote of a quoted value, so that '(body) and (body) are the same
+fn unquote(value: Value) -> Value {
+    match value {
+        Value::List(list) => {
+            if list.cells().len() == 2 && is_quote(list.cells().first().unwrap()) {
+                list.into_cells().pop().unwrap()
+            }
+            else {
+                Value::List(list)
+            }
+        },
+        other => other
+    }
+}
+
+//describes where two values differ, the path contains the list indices
+#[allow(clippy::needless_borrowed_reference)]
+fn difference(expected: &Value, actual: &Value, path: String) -> Option<String> {
+    match (expected, actual) {
+        (&Value::List(ref list_1), &Value::List(ref list_2)) => {
+            for (i, (elem_1, elem_2)) in list_1.cells().iter().zip(list_2.cells().iter()).enumerate() {
+                if elem_1 != elem_2 {
+                    return difference(elem_1, elem_2, format!("{}[{}]", path, i));
+                }
+            }
+            if list_1.cells().len() != list_2.cells().len() {
+                return Some(format!("{}expected {} elements, found {}", at(&path), list_1.cells().len(), list_2.cells().len()));
+            }
+            None
+        },
+        (value_1, value_2) => {
+            if value_1 == value_2 {
+                None
+            }
+            else {
+                Some(format!("{}expected {:?}, found {:?}", at(&path), value_1, value_2))
+            }
+        }
+    }
+}
+
+#[allow(clippy::len_zero)]
+fn at(path: &String) -> String {
+    if path.len() == 0 {
+        String::new()
+    }
+    else {
+        format!("at {}: ", path)
+    }
+}
\ No newline at end of file
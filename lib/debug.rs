@@ -0,0 +1,254 @@
+use std::io::{
+    stdin,
+    stdout,
+    Write
+};
+
+use error::Error;
+use list::List;
+use stack::Stack;
+use value::Value;
+
+//a hook that is called by List::eval, can be installed with Interpreter::set_debug_hook
+pub trait DebugHook {
+    //called after the scope of the list was pushed, before the list is evaluated
+    fn before_eval(&mut self, list: &List, stack: &mut Stack) -> Result<(), Error>;
+
+    //called with the result of the list, before its scope is popped
+    fn after_eval(&mut self, _list: &List, _result: &Result<Value, Error>, _stack: &mut Stack) -> Result<(), Error> {
+        Ok(())
+    }
+
+    //called by the builtin 'break'
+    fn on_break(&mut self, list: &List, stack: &mut Stack) -> Result<(), Error>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Breakpoint {
+    Function(String),
+    Line(usize)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StepMode {
+    Continue,
+    StepIn,
+    StepOver(usize),
+    StepOut(usize)
+}
+
+//an interactive debugger that reads its commands from stdin
+pub struct Debugger {
+    mode: StepMode,
+    breakpoints: Vec<Breakpoint>,
+    last_location: Option<(usize, usize)>
+}
+
+impl Debugger {
+    //the debugger pauses before the first list it sees
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Debugger {
+        Debugger {
+            mode: StepMode::StepIn,
+            breakpoints: Vec::new(),
+            last_location: None
+        }
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+    }
+
+    #[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+    fn should_pause(&mut self, list: &List, stack: &Stack) -> bool {
+        //line breakpoints only pause once per top level form
+        let location = stack.location();
+        let new_form = location != self.last_location;
+        self.last_location = location;
+        let depth = stack.size();
+        let stepped = match self.mode {
+            StepMode::Continue => false,
+            StepMode::StepIn => true,
+            StepMode::StepOver(paused_depth) => depth <= paused_depth,
+            StepMode::StepOut(paused_depth) => depth < paused_depth
+        };
+        if stepped {
+            return true;
+        }
+        for breakpoint in &self.breakpoints {
+            match breakpoint {
+                &Breakpoint::Function(ref name) => {
+                    match list.cells().first() {
+                        Some(&Value::Symbol(ref symbol)) if symbol == &name[..] => {
+                            return true;
+                        },
+                        Some(&Value::Variable(ref variable)) if variable.symbol() == &name[..] => {
+                            return true;
+                        },
+                        _ => {}
+                    }
+                },
+                &Breakpoint::Line(line) => {
+                    match location {
+                        Some((first, last)) if new_form && first <= line && line <= last => {
+                            return true;
+                        },
+                        _ => {}
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    #[allow(clippy::len_zero, clippy::match_ref_pats, clippy::needless_borrowed_reference, clippy::useless_format)]
+    fn pause(&mut self, list: &List, stack: &mut Stack) -> Result<(), Error> {
+        print_position(list, stack);
+        loop {
+            print!("(debug) ");
+            let _ = stdout().flush();
+            let mut line = String::new();
+            match stdin().read_line(&mut line) {
+                Ok(0) | Err(_) => { //stdin was closed, run the rest of the program without stopping
+                    self.mode = StepMode::Continue;
+                    self.breakpoints.clear();
+                    return Ok(());
+                },
+                Ok(_) => {}
+            }
+            let line = line.trim();
+            let (command, arg) = match line.find(' ') {
+                Some(index) => (&line[..index], line[index+1..].trim()),
+                None => (line, "")
+            };
+            match command {
+                "s" | "step" => {
+                    self.mode = StepMode::StepIn;
+                    return Ok(());
+                },
+                "n" | "next" => {
+                    self.mode = StepMode::StepOver(stack.size());
+                    return Ok(());
+                },
+                "o" | "out" => {
+                    self.mode = StepMode::StepOut(stack.size());
+                    return Ok(());
+                },
+                "c" | "continue" => {
+                    self.mode = StepMode::Continue;
+                    return Ok(());
+                },
+                "q" | "quit" => {
+                    self.mode = StepMode::Continue;
+                    return Err(Error::new_with_origin("debugger", format!("evaluation was aborted.")));
+                },
+                "b" | "break" => {
+                    if arg.len() == 0 {
+                        println!("usage: break <function name or line>");
+                        continue;
+                    }
+                    let breakpoint = match arg.parse::<usize>() {
+                        Ok(line) => Breakpoint::Line(line),
+                        Err(_) => Breakpoint::Function(arg.to_owned())
+                    };
+                    self.add_breakpoint(breakpoint);
+                },
+                "d" | "delete" => {
+                    match arg.parse::<usize>() {
+                        Ok(index) if index < self.breakpoints.len() => {
+                            self.breakpoints.remove(index);
+                        },
+                        _ => println!("usage: delete <breakpoint index>")
+                    }
+                },
+                "bl" | "breakpoints" => {
+                    for (i, breakpoint) in self.breakpoints.iter().enumerate() {
+                        match breakpoint {
+                            &Breakpoint::Function(ref name) => println!("  {}: function '{}'", i, name),
+                            &Breakpoint::Line(line) => println!("  {}: line {}", i, line)
+                        }
+                    }
+                },
+                "bt" | "stack" => {
+                    for (i, scope) in stack.scopes().iter().enumerate().rev() {
+                        let names = scope.variables().into_iter().map(|x| x.0.to_string()).collect::<Vec<String>>();
+                        println!("  #{} [{} variables] {}", i, names.len(), names.join(" "));
+                    }
+                },
+                "scope" => {
+                    let index = match arg.parse::<usize>() {
+                        Ok(index) => index,
+                        Err(_) => stack.scopes().len() - 1
+                    };
+                    match stack.scopes().get(index) {
+                        Some(scope) => {
+                            for (name, value) in scope.variables() {
+                                println!("  {} = {:?}", name, value);
+                            }
+                        },
+                        None => println!("there is no scope #{}.", index)
+                    }
+                },
+                "l" | "list" => {
+                    print_position(list, stack);
+                },
+                "p" | "print" => {
+                    let size = stack.size();
+                    let result = match List::from_string(arg.to_owned()) {
+                        Ok(code) => code.eval(stack, None),
+                        Err(err) => Err(err)
+                    };
+                    stack.truncate(size);
+                    match result {
+                        Ok(value) => println!("  {:?}", value),
+                        Err(err) => print!("{}", err)
+                    }
+                },
+                "h" | "help" | "" => {
+                    print_help();
+                },
+                unknown => {
+                    println!("unknown command '{}', type 'help' for a list of commands.", unknown);
+                }
+            }
+        }
+    }
+}
+
+impl DebugHook for Debugger {
+    fn before_eval(&mut self, list: &List, stack: &mut Stack) -> Result<(), Error> {
+        if self.should_pause(list, stack) {
+            return self.pause(list, stack);
+        }
+        Ok(())
+    }
+
+    fn on_break(&mut self, list: &List, stack: &mut Stack) -> Result<(), Error> {
+        println!("reached 'break'.");
+        self.pause(list, stack)
+    }
+}
+
+fn print_position(list: &List, stack: &Stack) {
+    match stack.location() {
+        Some((first, last)) => println!("[depth {}, lines {}-{}] {}", stack.size(), first, last, Value::List(list.clone())),
+        None => println!("[depth {}] {}", stack.size(), Value::List(list.clone()))
+    }
+}
+
+fn print_help() {
+    println!("  s, step          evaluate the next list, stepping into calls");
+    println!("  n, next          evaluate the next list on the same or a lower depth");
+    println!("  o, out           continue until the current list returns");
+    println!("  c, continue      continue until a breakpoint is reached");
+    println!("  b, break <x>     add a breakpoint for a function name or a line");
+    println!("  d, delete <i>    remove the breakpoint with the given index");
+    println!("  bl, breakpoints  list all breakpoints");
+    println!("  bt, stack        show all scopes on the stack");
+    println!("  scope [i]        show the variables of a scope, default is the top scope");
+    println!("  l, list          show the current list");
+    println!("  p, print <code>  evaluate code in the current scope");
+    println!("  q, quit          abort the evaluation");
+}
\ No newline at end of file
@@ -2,7 +2,7 @@ use std::fmt::Display;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Error {
     origin: Option<String>,
     message: String,
@@ -26,6 +26,10 @@ impl Error {
         }
     }
 
+    pub fn message(&self) -> &String {
+        &self.message
+    }
+
     pub fn set_origin(mut self, origin: String) -> Error {
         self.origin = Some(origin);
         self
@@ -56,4 +60,4 @@ impl Display for Error {
         }
         Ok(())
     }
-}
+}
\ No newline at end of file
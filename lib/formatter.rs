@@ -0,0 +1,323 @@
+use num_bigint::BigInt;
+
+use error::Error;
+use number::Number;
+use symbol::Symbol;
+use value::Value;
+
+//the format language of the 'format' builtin:
+//$$ and {} are replaced by the next argument, {0} by the first one and {name} by the value of the key :name
+//in the map that is given as last argument, {{ and }} are escaped braces
+//after a colon follows the format specification [[fill]align][+][0][width][.precision][type]
+//with the alignments < ^ >, and the types ? [debug], x, X, o, b [radix of integers] and e [scientific notation]
+
+//the largest width and precision, like in the format strings of Rust
+const MAX_WIDTH: usize = u16::MAX as usize;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Align {
+    Left,
+    Center,
+    Right
+}
+
+#[derive(Debug, Clone)]
+enum Argument {
+    Next,
+    Index(usize),
+    Name(String)
+}
+
+#[derive(Debug, Clone)]
+struct Spec {
+    fill: char,
+    align: Option<Align>,
+    sign: bool,
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+    kind: Option<char>
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Text(String),
+    //the source of the placeholder is kept for the error messages
+    Placeholder(String, Argument, Spec)
+}
+
+//formats the template with the arguments, the template is checked completely before anything is formatted
+#[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference, clippy::single_match, clippy::useless_format)]
+pub fn format_template(template: &str, args: &[Value]) -> Result<String, Error> {
+    let segments = parse(template)?;
+    let named = segments.iter().any(|x| matches!(x, &Segment::Placeholder(_, Argument::Name(_), _)));
+    let positional = match named {
+        true => match args.last() {
+            Some(&Value::Map(_)) => &args[..args.len() - 1],
+            _ => return Err(Error::new(format!("the template contains named placeholders, but the last argument is not a map.")))
+        },
+        false => args
+    };
+    let mut next = 0;
+    let mut used = vec!(false; positional.len());
+    let mut resolved = Vec::new();
+    for segment in &segments {
+        match segment {
+            &Segment::Text(_) => resolved.push(None),
+            &Segment::Placeholder(ref source, ref argument, _) => {
+                let index = match argument {
+                    &Argument::Next => {
+                        next += 1;
+                        Some(next - 1)
+                    },
+                    &Argument::Index(index) => Some(index),
+                    &Argument::Name(_) => None
+                };
+                let value = match (index, argument, args.last()) {
+                    (Some(index), _, _) => match positional.get(index) {
+                        Some(value) => {
+                            used[index] = true;
+                            value
+                        },
+                        None => return Err(invalid(source, format!("there is no argument {}, found {} arguments", index, positional.len())))
+                    },
+                    (None, &Argument::Name(ref name), Some(&Value::Map(ref map))) => match map.get(&Value::Symbol(Symbol::from(format!(":{}", name)))) {
+                        Some(value) => value,
+                        None => return Err(invalid(source, format!("the map has no key :{}", name)))
+                    },
+                    _ => unreachable!()
+                };
+                resolved.push(Some(value));
+            }
+        }
+    }
+    match used.iter().position(|x| !x) {
+        Some(index) => return Err(Error::new(format!("argument {} is never used.", index))),
+        None => {}
+    }
+    let mut result = String::new();
+    for (segment, value) in segments.iter().zip(resolved) {
+        match (segment, value) {
+            (&Segment::Text(ref text), _) => result.push_str(text),
+            (&Segment::Placeholder(ref source, _, ref spec), Some(value)) => result.push_str(&format_value(value, spec, source)?),
+            _ => unreachable!()
+        }
+    }
+    Ok(result)
+}
+
+fn invalid(source: &str, reason: String) -> Error {
+    Error::new(format!("invalid placeholder '{}': {}.", source, reason))
+}
+
+#[allow(clippy::useless_format)]
+fn parse(template: &str) -> Result<Vec<Segment>, Error> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '$' if chars.peek() == Some(&'$') => {
+                chars.next();
+                segments.push(Segment::Text(::std::mem::take(&mut text)));
+                segments.push(Segment::Placeholder(format!("$$"), Argument::Next, parse_spec("", "$$")?));
+            },
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            },
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            },
+            '}' => return Err(Error::new(format!("unmatched '}}' in the template, use '}}}}' for a brace."))),
+            '{' => {
+                let mut inner = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(ch) => inner.push(ch),
+                        None => return Err(Error::new(format!("unclosed placeholder '{{{}' in the template, use '{{{{' for a brace.", inner)))
+                    }
+                }
+                let source = format!("{{{}}}", inner);
+                let (argument_str, spec_str) = match inner.find(':') {
+                    Some(index) => (&inner[..index], &inner[index + 1..]),
+                    None => (&inner[..], "")
+                };
+                let argument = match argument_str {
+                    "" => Argument::Next,
+                    s if s.chars().all(|x| x.is_ascii_digit()) => match s.parse() {
+                        Ok(index) => Argument::Index(index),
+                        Err(_) => return Err(invalid(&source, format!("the index {} is too large", s)))
+                    },
+                    s if s.chars().all(|x| x.is_alphanumeric() || x == '-' || x == '_') => Argument::Name(s.to_owned()),
+                    s => return Err(invalid(&source, format!("'{}' is neither an index nor a name", s)))
+                };
+                segments.push(Segment::Text(::std::mem::take(&mut text)));
+                segments.push(Segment::Placeholder(source.clone(), argument, parse_spec(spec_str, &source)?));
+            },
+            ch => text.push(ch)
+        }
+    }
+    segments.push(Segment::Text(text));
+    Ok(segments)
+}
+
+#[allow(clippy::len_zero, clippy::useless_format)]
+fn parse_spec(spec: &str, source: &str) -> Result<Spec, Error> {
+    let mut result = Spec {
+        fill: ' ',
+        align: None,
+        sign: false,
+        zero: false,
+        width: 0,
+        precision: None,
+        kind: None
+    };
+    let chars = spec.chars().collect::<Vec<_>>();
+    let mut i = 0;
+    match (chars.first().cloned(), chars.get(1).and_then(|x| to_align(*x))) {
+        (Some(fill), Some(align)) => {
+            result.fill = fill;
+            result.align = Some(align);
+            i = 2;
+        },
+        (Some(ch), _) if to_align(ch).is_some() => {
+            result.align = to_align(ch);
+            i = 1;
+        },
+        _ => {}
+    }
+    if chars.get(i) == Some(&'+') {
+        result.sign = true;
+        i += 1;
+    }
+    if chars.get(i) == Some(&'0') {
+        result.zero = true;
+        i += 1;
+    }
+    let width = chars[i..].iter().take_while(|x| x.is_ascii_digit()).collect::<String>();
+    i += width.len();
+    if width.len() > 0 {
+        result.width = match width.parse() {
+            Ok(width) if width <= MAX_WIDTH => width,
+            _ => return Err(invalid(source, format!("the width {} is larger than {}", width, MAX_WIDTH)))
+        };
+    }
+    if chars.get(i) == Some(&'.') {
+        let precision = chars[i + 1..].iter().take_while(|x| x.is_ascii_digit()).collect::<String>();
+        if precision.len() == 0 {
+            return Err(invalid(source, format!("expected a number after '.'")));
+        }
+        i += precision.len() + 1;
+        result.precision = match precision.parse() {
+            Ok(precision) if precision <= MAX_WIDTH => Some(precision),
+            _ => return Err(invalid(source, format!("the precision {} is larger than {}", precision, MAX_WIDTH)))
+        };
+    }
+    match chars.get(i) {
+        Some(&kind) if "?xXobe".contains(kind) => {
+            result.kind = Some(kind);
+            i += 1;
+        },
+        _ => {}
+    }
+    if i < chars.len() {
+        return Err(invalid(source, format!("unexpected '{}' in the format specification", chars[i..].iter().collect::<String>())));
+    }
+    Ok(result)
+}
+
+fn to_align(ch: char) -> Option<Align> {
+    match ch {
+        '<' => Some(Align::Left),
+        '^' => Some(Align::Center),
+        '>' => Some(Align::Right),
+        _ => None
+    }
+}
+
+#[allow(clippy::needless_borrowed_reference)]
+fn format_value(value: &Value, spec: &Spec, source: &str) -> Result<String, Error> {
+    let number = Number::from_value(value);
+    let text = match (spec.kind, &number) {
+        (Some('?'), _) if value.is_list_and_string() => format!("{:?}", string_of(value)),
+        (Some('?'), _) => format!("{:?}", value),
+        (Some(radix @ 'x'), &Some(ref n)) | (Some(radix @ 'X'), &Some(ref n)) | (Some(radix @ 'o'), &Some(ref n)) | (Some(radix @ 'b'), &Some(ref n)) => {
+            let integer = match n.to_integer() {
+                Some(integer) => integer,
+                None => return Err(invalid(source, format!("'{}' expects an integer, found {}", radix, value.type_str())))
+            };
+            format_radix(&integer, radix)
+        },
+        (Some('e'), &Some(ref n)) => match spec.precision {
+            Some(precision) => format!("{:.*e}", precision, n.to_float()),
+            None => format!("{:e}", n.to_float())
+        },
+        (Some(kind), &None) => return Err(invalid(source, format!("'{}' expects a number, found {}", kind, value.type_str()))),
+        (_, &Some(ref n)) => match spec.precision {
+            Some(precision) => format!("{:.*}", precision, n.to_float()),
+            None => format!("{}", value)
+        },
+        (_, &None) => {
+            let text = match value.is_list_and_string() {
+                true => string_of(value),
+                false => format!("{}", value)
+            };
+            match spec.precision {
+                Some(precision) => text.chars().take(precision).collect(),
+                None => text
+            }
+        }
+    };
+    let text = match (spec.sign, &number) {
+        (true, &Some(_)) if !text.starts_with('-') => format!("+{}", text),
+        _ => text
+    };
+    Ok(pad(text, spec, number.is_some()))
+}
+
+fn format_radix(integer: &BigInt, radix: char) -> String {
+    match radix {
+        'x' => integer.to_str_radix(16),
+        'X' => integer.to_str_radix(16).to_uppercase(),
+        'o' => integer.to_str_radix(8),
+        _ => integer.to_str_radix(2)
+    }
+}
+
+#[allow(clippy::needless_borrowed_reference)]
+fn string_of(value: &Value) -> String {
+    match value {
+        &Value::List(ref list) => list.cells().iter().map(|x| format!("{}", x)).collect(),
+        _ => String::new()
+    }
+}
+
+//numbers are aligned right and zero padding is put after the sign, everything else is aligned left
+fn pad(text: String, spec: &Spec, is_number: bool) -> String {
+    let len = text.chars().count();
+    if len >= spec.width {
+        return text;
+    }
+    let missing = spec.width - len;
+    if spec.zero && spec.align.is_none() && is_number {
+        let (sign, digits) = match text.starts_with('-') || text.starts_with('+') {
+            true => text.split_at(1),
+            false => ("", &text[..])
+        };
+        return format!("{}{}{}", sign, "0".repeat(missing), digits);
+    }
+    let align = match spec.align {
+        Some(align) => align,
+        None if is_number => Align::Right,
+        None => Align::Left
+    };
+    let fill = |count: usize| spec.fill.to_string().repeat(count);
+    match align {
+        Align::Left => format!("{}{}", text, fill(missing)),
+        Align::Right => format!("{}{}", fill(missing), text),
+        Align::Center => format!("{}{}{}", fill(missing / 2), text, fill(missing - missing / 2))
+    }
+}
\ No newline at end of file
@@ -3,6 +3,7 @@ use list::resolve;
 use error::Error;
 use value::Value;
 use stack::Stack;
+use number::Number;
 use ::FLOAT;
 
 use corelib::math::{
@@ -14,7 +15,55 @@ use corelib::math::{
     cos,
     tan,
     count,
-    modulo
+    modulo,
+    numerator,
+    denominator,
+    exact_to_inexact,
+    rationalize,
+    asin,
+    acos,
+    atan,
+    atan2,
+    sinh,
+    cosh,
+    tanh,
+    asinh,
+    acosh,
+    atanh,
+    exp,
+    log,
+    log2,
+    log10,
+    pow,
+    sqrt,
+    cbrt,
+    abs,
+    floor,
+    ceil,
+    round,
+    trunc,
+    min,
+    max,
+    quot,
+    rem,
+    gcd,
+    lcm,
+    bit_and,
+    bit_or,
+    bit_xor,
+    bit_not,
+    shl,
+    shr
+};
+use corelib::parallel::{
+    pmap,
+    future,
+    spawn_async,
+    await_fn,
+    promise,
+    deliver,
+    realized,
+    pool_size
 };
 use corelib::program::{
     set,
@@ -28,6 +77,9 @@ use corelib::program::{
     while_loop,
     spawn,
     eval as eval_fn,
+    optimize,
+    gc_fn,
+    gc_stats,
     puts,
     putsln,
     throw,
@@ -35,6 +87,11 @@ use corelib::program::{
     type_fn,
     try_rename,
     format,
+    break_fn,
+    profile,
+    partial,
+    trace,
+    untrace,
 };
 use corelib::listops::{
     last,
@@ -57,9 +114,90 @@ use corelib::listops::{
     intersect,
     zip,
     rev,
-    sort
+    sort,
+    sort_desc,
+    sort_by,
+    sort_by_desc,
+    sort_with,
+    map_indexed,
+    fold_right,
+    group_by,
+    partition,
+    partition_by,
+    chunk,
+    windows,
+    drop_while,
+    flat_map,
+    interleave,
+    frequencies,
+    min_by,
+    max_by,
+    distinct_by,
+    dedupe
+};
+use corelib::atoms::{
+    atom,
+    deref,
+    reset,
+    swap,
+    compare_and_set
+};
+use corelib::binding::{
+    let_fn,
+    let_star,
+    letrec,
+    match_fn
+};
+use corelib::maps::{
+    dict,
+    get,
+    assoc,
+    dissoc,
+    has_key,
+    keys,
+    vals
+};
+use corelib::records::{
+    defrecord,
+    make_record,
+    is_record,
+    record_get,
+    record_with
+};
+use corelib::array::{
+    shape,
+    reshape,
+    flatten,
+    transpose,
+    outer,
+    iota,
+    reduce_axis,
+    scan,
+    rotate
+};
+use corelib::seqs::{
+    range,
+    iterate,
+    repeat,
+    cycle,
+    take,
+    drop,
+    take_while,
+    into_list
+};
+use corelib::symbols::{
+    gensym,
+    symbol_to_string,
+    string_to_symbol
+};
+use corelib::testing::{
+    deftest,
+    assert,
+    assert_eq,
+    assert_throws
 };
 use corelib::comp::{
+    compare,
     eq,
     ne,
     lt,
@@ -71,6 +209,10 @@ use corelib::comp::{
     or
 };
 
+//the signature every builtin function has
+pub type Builtin = fn(&List, &mut Stack) -> Result<Value, Error>;
+
+#[allow(clippy::needless_borrowed_reference)]
 pub fn eval(list: &List, stack: &mut Stack) -> Result<Option<Value>, Error> {
     let function = match list.cells().first().unwrap() { //unwrap, because eval checks for empty list
         &Value::Symbol(ref func) => func,
@@ -78,74 +220,210 @@ pub fn eval(list: &List, stack: &mut Stack) -> Result<Option<Value>, Error> {
             return Ok(None);
         }
     };
-    let result = match &function[..] {
-        "lambda" => lambda(list, stack),
-        "seq" => seq(list, stack),
-        "set" | "$" => set(list, stack),
-        "global" => global(list, stack),
-        "quote" => quote(list, stack),
-        "add" | "+" => add(list, stack),
-        "sub" | "-" => sub(list, stack),
-        "mul" | "*" => mul(list, stack),
-        "div" | "/" => div(list, stack),
-        "sin" => sin(list, stack),
-        "cos" => cos(list, stack),
-        "tan" => tan(list, stack),
-        "last" => last(list, stack),
-        "init" => init(list, stack),
-        "tail" => tail(list, stack),
-        "len" => len(list, stack),
-        "nth" => nth(list, stack),
-        "cons" => cons(list, stack),
-        "cond" => cond(list, stack),
-        "printfmt" => printfmt(list, stack),
-        "print" => print(list, stack),
-        "eq" | "=" => eq(list, stack),
-        "ne" | "!=" => ne(list, stack),
-        "lt" | "<" => lt(list, stack),
-        "gt" | ">" => gt(list, stack),
-        "le" | "<=" => le(list, stack),
-        "ge" | ">=" => ge(list, stack),
-        "map" | "%" => map(list, stack),
-        "fold" | "\\" => fold(list, stack),
-        "any" => any(list, stack),
-        "all" => all(list, stack),
-        "filter" | "_" => filter(list, stack),
-        "count" | ".." => count(list, stack),
-        "mod" => modulo(list, stack),
-        "and" => and(list, stack),
-        "or" => or(list, stack),
-        "not" => not(list, stack),
-        "while" => while_loop(list, stack),
-        "spawn" => spawn(list, stack),
-        "eval" => eval_fn(list, stack),
-        "append" => append(list, stack),
-        "unique" => unique(list, stack),
-        "expand" => expand(list, stack),
-        "puts" => puts(list, stack),
-        "putsln" => putsln(list, stack),
-        "throw" => throw(list, stack),
-        "try" => try(list, stack),
-        "type" => type_fn(list, stack),
-        "try_rename" => try_rename(list, stack),
-        "format" => format(list, stack),
-        "find" => find(list, stack),
-        "split_at" => split_at(list, stack),
-        "combine" => combine(list, stack),
-        "intersect" => intersect(list, stack),
-        "zip" => zip(list, stack),
-        "rev" => rev(list, stack),
-        "sort" => sort(list, stack),
-        _ => {
+    let builtin = match lookup(&function[..]) {
+        Some(builtin) => builtin,
+        None => {
             return Ok(None)
         }
     };
+    stack.profile_enter(function);
+    let traced = stack.trace_enter(function, &list.cells()[1..], false);
+    let result = builtin(list, stack);
+    stack.trace_leave(traced, function, &result);
+    stack.profile_leave();
     match result {
         Ok(value) => Ok(Some(value)),
         Err(err) => Err(err)
     }
 }
 
+//finds the builtin function with the given name or alias
+pub fn lookup(name: &str) -> Option<Builtin> {
+    let builtin: Builtin = match name {
+        "lambda" => lambda,
+        "partial" => partial,
+        "seq" => seq,
+        "set" | "$" => set,
+        "global" => global,
+        "let" => let_fn,
+        "let*" => let_star,
+        "letrec" => letrec,
+        "match" => match_fn,
+        "quote" => quote,
+        "add" | "+" => add,
+        "sub" | "-" => sub,
+        "mul" | "*" => mul,
+        "div" | "/" => div,
+        "sin" => sin,
+        "cos" => cos,
+        "tan" => tan,
+        "asin" => asin,
+        "acos" => acos,
+        "atan" => atan,
+        "atan2" => atan2,
+        "sinh" => sinh,
+        "cosh" => cosh,
+        "tanh" => tanh,
+        "asinh" => asinh,
+        "acosh" => acosh,
+        "atanh" => atanh,
+        "exp" => exp,
+        "log" => log,
+        "log2" => log2,
+        "log10" => log10,
+        "pow" => pow,
+        "sqrt" => sqrt,
+        "cbrt" => cbrt,
+        "abs" => abs,
+        "floor" => floor,
+        "ceil" => ceil,
+        "round" => round,
+        "trunc" => trunc,
+        "min" => min,
+        "max" => max,
+        "quot" => quot,
+        "rem" => rem,
+        "gcd" => gcd,
+        "lcm" => lcm,
+        "bit-and" => bit_and,
+        "bit-or" => bit_or,
+        "bit-xor" => bit_xor,
+        "bit-not" => bit_not,
+        "shl" => shl,
+        "shr" => shr,
+        "last" => last,
+        "init" => init,
+        "tail" => tail,
+        "len" => len,
+        "nth" => nth,
+        "cons" => cons,
+        "cond" => cond,
+        "printfmt" => printfmt,
+        "print" => print,
+        "eq" | "=" => eq,
+        "ne" | "!=" => ne,
+        "lt" | "<" => lt,
+        "gt" | ">" => gt,
+        "le" | "<=" => le,
+        "ge" | ">=" => ge,
+        "map" | "%" => map,
+        "fold" | "\\" => fold,
+        "any" => any,
+        "all" => all,
+        "filter" | "_" => filter,
+        "count" | ".." => count,
+        "mod" => modulo,
+        "numerator" => numerator,
+        "denominator" => denominator,
+        "exact->inexact" => exact_to_inexact,
+        "rationalize" => rationalize,
+        "and" => and,
+        "or" => or,
+        "not" => not,
+        "while" => while_loop,
+        "spawn" => spawn,
+        "spawn-async" => spawn_async,
+        "pmap" => pmap,
+        "future" => future,
+        "await" => await_fn,
+        "promise" => promise,
+        "deliver" => deliver,
+        "realized?" => realized,
+        "pool-size" => pool_size,
+        "eval" => eval_fn,
+        "optimize" => optimize,
+        "gc" => gc_fn,
+        "gc-stats" => gc_stats,
+        "append" => append,
+        "unique" => unique,
+        "expand" | "reductions" => expand,
+        "puts" => puts,
+        "putsln" => putsln,
+        "throw" => throw,
+        "try" => try,
+        "type" => type_fn,
+        "try_rename" => try_rename,
+        "format" => format,
+        "find" => find,
+        "split_at" => split_at,
+        "combine" => combine,
+        "intersect" => intersect,
+        "zip" => zip,
+        "rev" => rev,
+        "sort" => sort,
+        "sort-desc" => sort_desc,
+        "sort-by" => sort_by,
+        "sort-by-desc" => sort_by_desc,
+        "sort-with" => sort_with,
+        "compare" => compare,
+        "dict" => dict,
+        "get" => get,
+        "assoc" => assoc,
+        "dissoc" => dissoc,
+        "has-key" => has_key,
+        "keys" => keys,
+        "vals" => vals,
+        "atom" => atom,
+        "deref" => deref,
+        "reset!" => reset,
+        "swap!" => swap,
+        "compare-and-set!" => compare_and_set,
+        "defrecord" => defrecord,
+        "make-record" => make_record,
+        "record?" => is_record,
+        "record-get" => record_get,
+        "record-with" => record_with,
+        "range" => range,
+        "iterate" => iterate,
+        "repeat" => repeat,
+        "cycle" => cycle,
+        "take" => take,
+        "drop" => drop,
+        "take-while" => take_while,
+        "into-list" | "doall" => into_list,
+        "gensym" => gensym,
+        "symbol->string" => symbol_to_string,
+        "string->symbol" => string_to_symbol,
+        "map-indexed" => map_indexed,
+        "fold-right" => fold_right,
+        "group-by" => group_by,
+        "partition" => partition,
+        "partition-by" => partition_by,
+        "chunk" => chunk,
+        "windows" => windows,
+        "drop-while" => drop_while,
+        "flat-map" => flat_map,
+        "interleave" => interleave,
+        "frequencies" => frequencies,
+        "min-by" => min_by,
+        "max-by" => max_by,
+        "distinct-by" => distinct_by,
+        "dedupe" => dedupe,
+        "shape" => shape,
+        "reshape" => reshape,
+        "flatten" => flatten,
+        "transpose" => transpose,
+        "outer" => outer,
+        "iota" => iota,
+        "reduce-axis" => reduce_axis,
+        "scan" => scan,
+        "rotate" => rotate,
+        "break" => break_fn,
+        "profile" => profile,
+        "trace" => trace,
+        "untrace" => untrace,
+        "deftest" => deftest,
+        "assert" => assert,
+        "assert-eq" => assert_eq,
+        "assert-throws" => assert_throws,
+        _ => {
+            return None
+        }
+    };
+    Some(builtin)
+}
+
+#[allow(clippy::len_zero)]
 pub fn invalid_types(types: Vec<&Value>, fn_name: &'static str) -> Result<(), Error> {
     let mut type_str = String::new();
     for t in types {
@@ -159,6 +437,7 @@ pub fn invalid_types(types: Vec<&Value>, fn_name: &'static str) -> Result<(), Er
 }
 
 //might seem hacky, but is the only way I can use pattern matching
+#[allow(clippy::needless_question_mark)]
 pub fn resolve_argument(list: &List, stack: &mut Stack, fn_name: &'static str) -> Result<Value, Error> {
     assert_length(list, 1, fn_name)?;
     Ok(resolve(list.cells().get(1).unwrap().clone(), stack, fn_name)?)
@@ -191,6 +470,59 @@ pub fn resolve_four_arguments(list: &List, stack: &mut Stack, fn_name: &'static
     ))
 }
 
+//lazy sequences are computed and turned into lists, other values stay unchanged
+pub fn force(value: Value, stack: &mut Stack) -> Result<Value, Error> {
+    match value {
+        Value::LazySeq(seq) => Ok(Value::List(List::from_cells(seq.force(stack)?))),
+        other => Ok(other)
+    }
+}
+
+//like resolve_argument, but lazy sequences are forced, for functions that need the whole list
+pub fn resolve_forced_argument(list: &List, stack: &mut Stack, fn_name: &'static str) -> Result<Value, Error> {
+    let op_1 = resolve_argument(list, stack, fn_name)?;
+    force(op_1, stack)
+}
+
+pub fn resolve_two_forced_arguments(list: &List, stack: &mut Stack, fn_name: &'static str) -> Result<(Value, Value), Error> {
+    let (op_1, op_2) = resolve_two_arguments(list, stack, fn_name)?;
+    Ok((force(op_1, stack)?, force(op_2, stack)?))
+}
+
+pub fn resolve_three_forced_arguments(list: &List, stack: &mut Stack, fn_name: &'static str) -> Result<(Value, Value, Value), Error> {
+    let (op_1, op_2, op_3) = resolve_three_arguments(list, stack, fn_name)?;
+    Ok((force(op_1, stack)?, force(op_2, stack)?, force(op_3, stack)?))
+}
+
+//resolves all arguments, for functions that take any number of them
+pub fn resolve_arguments(list: &List, stack: &mut Stack, fn_name: &'static str) -> Result<Vec<Value>, Error> {
+    let mut result = Vec::with_capacity(list.cells().len() - 1);
+    for cell in list.cells().iter().skip(1) {
+        result.push(resolve(cell.clone(), stack, fn_name)?);
+    }
+    Ok(result)
+}
+
+//like resolve_arguments, but lazy sequences are forced
+pub fn resolve_forced_arguments(list: &List, stack: &mut Stack, fn_name: &'static str) -> Result<Vec<Value>, Error> {
+    let values = resolve_arguments(list, stack, fn_name)?;
+    values.into_iter().map(|x| force(x, stack)).collect()
+}
+
+//resolves all arguments, which have to be numbers
+pub fn resolve_numbers(list: &List, stack: &mut Stack, fn_name: &'static str) -> Result<Vec<Number>, Error> {
+    let values = resolve_arguments(list, stack, fn_name)?;
+    let mut numbers = Vec::with_capacity(values.len());
+    for value in &values {
+        match Number::from_value(value) {
+            Some(n) => numbers.push(n),
+            None => invalid_types(values.iter().collect(), fn_name)?
+        }
+    }
+    Ok(numbers)
+}
+
+#[allow(clippy::needless_return)]
 pub fn assert_min_length(list: &List, length: usize, fn_name: &'static str) -> Result<(), Error> {
     let len = list.cells().len() - 1;
     if len < length {
@@ -199,6 +531,7 @@ pub fn assert_min_length(list: &List, length: usize, fn_name: &'static str) -> R
     return Ok(())
 }
 
+#[allow(clippy::needless_return)]
 pub fn assert_length(list: &List, length: usize, fn_name: &'static str) -> Result<(), Error> {
     let len = list.cells().len() - 1;
     if len != length {
@@ -208,9 +541,5 @@ pub fn assert_length(list: &List, length: usize, fn_name: &'static str) -> Resul
 }
 
 pub fn to_float(value: Value) -> Option<FLOAT> {
-    match value {
-        Value::Integer(i) => Some(i as FLOAT),
-        Value::Float(f) => Some(f),
-        _ => None
-    }
+    Number::from_value(&value).map(|x| x.to_float())
 }
\ No newline at end of file
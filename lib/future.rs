@@ -0,0 +1,133 @@
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::sync::{
+    Arc,
+    Condvar,
+    Mutex,
+    MutexGuard
+};
+use std::time::Duration;
+use std::time::Instant;
+
+use error::Error;
+use interpreter::Interpreter;
+use list::List;
+use pool;
+use scope::Scope;
+use value::Value;
+
+//how long a waiting thread sleeps before it looks for queued jobs again
+const POLL_INTERVAL: u64 = 10;
+
+//the result, none until it is there
+type Slot = Mutex<Option<Result<Value, Error>>>;
+
+//the result of code that runs on the thread pool or of a promise that is delivered once
+//clones refer to the same result, two futures are only equal if they are the same reference
+#[derive(Clone)]
+pub struct Future {
+    state: Arc<(Slot, Condvar)>
+}
+
+impl Future {
+    //a future without a result, a promise
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Future {
+        Future {
+            state: Arc::new((Mutex::new(None), Condvar::new()))
+        }
+    }
+
+    //runs the function on the thread pool, a panic in it becomes an error
+    //the job stops counting as a running thread before the result is there, so a collection after await is not skipped
+    #[allow(clippy::useless_format)]
+    pub fn run<F>(function: F) -> Future where F: FnOnce() -> Result<Value, Error> + Send + 'static {
+        let future = Future::new();
+        let result = future.clone();
+        pool::submit(move |guard| {
+            let value = panic::catch_unwind(AssertUnwindSafe(function));
+            drop(guard);
+            result.complete(value.unwrap_or_else(|_| Err(Error::new(format!("the thread panicked.")))));
+        });
+        future
+    }
+
+    //evaluates the code on the thread pool in an interpreter that starts with a copy of the variables
+    pub fn spawn(code: List, globals: Arc<Scope>) -> Future {
+        Future::run(move || Interpreter::from_global((*globals).clone()).eval(code))
+    }
+
+    //a panic in another thread does not make the result unusable
+    fn lock(&self) -> MutexGuard<'_, Option<Result<Value, Error>>> {
+        self.state.0.lock().unwrap_or_else(|x| x.into_inner())
+    }
+
+    //sets the result if there is none yet, false if the future already has one
+    pub fn complete(&self, result: Result<Value, Error>) -> bool {
+        let mut guard = self.lock();
+        if guard.is_some() {
+            return false;
+        }
+        *guard = Some(result);
+        self.state.1.notify_all();
+        true
+    }
+
+    pub fn is_realized(&self) -> bool {
+        self.lock().is_some()
+    }
+
+    //blocks until the result is there or the timeout ran out
+    //a worker of the pool runs queued jobs while it waits without a timeout, see pool
+    #[allow(clippy::single_match)]
+    pub fn wait(&self, timeout: Option<Duration>) -> Option<Result<Value, Error>> {
+        let start = Instant::now();
+        loop {
+            match *self.lock() {
+                Some(ref result) => return Some(result.clone()),
+                None => {}
+            }
+            let remaining = match timeout {
+                Some(timeout) if start.elapsed() >= timeout => return None,
+                Some(timeout) => (timeout - start.elapsed()).min(Duration::from_millis(POLL_INTERVAL)),
+                None => Duration::from_millis(POLL_INTERVAL)
+            };
+            if timeout.is_none() && pool::help() {
+                continue;
+            }
+            let guard = self.lock();
+            if guard.is_none() {
+                let _ = self.state.1.wait_timeout(guard, remaining);
+            }
+        }
+    }
+}
+
+impl PartialEq for Future {
+    fn eq(&self, other: &Future) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+impl PartialOrd for Future {
+    fn partial_cmp(&self, other: &Future) -> Option<Ordering> {
+        match self == other {
+            true => Some(Ordering::Equal),
+            false => None
+        }
+    }
+}
+
+impl Debug for Future {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self.lock() {
+            Some(Ok(ref value)) => write!(f, "Future({:?})", value),
+            Some(Err(ref err)) => write!(f, "Future(error: {})", err.message()),
+            None => write!(f, "Future(pending)")
+        }
+    }
+}
\ No newline at end of file
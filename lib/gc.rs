@@ -0,0 +1,266 @@
+use atom::AtomCell;
+use list::Chunk;
+use value::Value;
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::mem;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::OnceLock;
+use std::sync::Weak;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+//values are reference counted, so only cycles need a collector: an atom that holds a value which refers to the atom,
+//or a list chunk that contains a list of the same chunk, which cons only makes when two threads cons at the same time.
+//atoms and chunks become candidates when such a value is written into them, a collection traces the values that
+//are reachable from the candidates and compares the references it found with the reference counts:
+//a value with more references than the traced ones is used from outside, the values that are not reachable
+//from such a value only refer to each other and are cleared, which breaks their cycles
+
+//the number of candidates after which a collection starts by itself, it grows with the candidates that survive
+const MIN_THRESHOLD: usize = 10000;
+
+//the number of atoms that are alive
+static ATOMS: AtomicUsize = AtomicUsize::new(0);
+//the number of interpreter threads that run besides the main one, collections are skipped while there are any
+static THREADS: AtomicUsize = AtomicUsize::new(0);
+
+//a value that can be part of a cycle, it is referenced weakly, so the collector does not keep it alive
+#[derive(Clone)]
+enum Node {
+    Atom(Weak<AtomCell>),
+    Chunk(Weak<Chunk>)
+}
+
+impl Node {
+    #[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+    fn address(&self) -> usize {
+        match self {
+            &Node::Atom(ref cell) => cell.as_ptr() as usize,
+            &Node::Chunk(ref chunk) => chunk.as_ptr() as usize
+        }
+    }
+
+    #[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+    fn strong_count(&self) -> usize {
+        match self {
+            &Node::Atom(ref cell) => cell.strong_count(),
+            &Node::Chunk(ref chunk) => chunk.strong_count()
+        }
+    }
+
+    //the nodes that the values of this node refer to, none if another thread holds the lock of an atom
+    #[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference, clippy::single_match)]
+    fn children(&self) -> Option<Vec<Node>> {
+        let mut children = Vec::new();
+        match self {
+            &Node::Atom(ref cell) => match cell.upgrade() {
+                Some(cell) => match cell.try_lock() {
+                    Some(value) => references(&value, &mut children),
+                    None => return None
+                },
+                None => {}
+            },
+            &Node::Chunk(ref chunk) => match chunk.upgrade() {
+                Some(chunk) => for value in chunk.values() {
+                    references(value, &mut children);
+                },
+                None => {}
+            }
+        }
+        Some(children)
+    }
+}
+
+//the atoms and chunks a value refers to directly or through maps, records and lambdas
+//lazy sequences are not traced, so the values in them always count as used from outside
+#[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+fn references(value: &Value, nodes: &mut Vec<Node>) {
+    match value {
+        &Value::List(ref list) => nodes.push(Node::Chunk(list.chunk())),
+        &Value::Atom(ref atom) => nodes.push(Node::Atom(atom.cell())),
+        &Value::Lambda(ref lambda) => {
+            let (body, bound) = lambda.values();
+            nodes.push(Node::Chunk(body.chunk()));
+            for value in bound {
+                references(value, nodes);
+            }
+        },
+        &Value::Map(ref map) => for &(ref key, ref value) in map.entries() {
+            references(key, nodes);
+            references(value, nodes);
+        },
+        &Value::Record(ref record) => for &(_, ref value) in record.fields() {
+            references(value, nodes);
+        },
+        _ => {}
+    }
+}
+
+//true if the value can refer to atoms or chunks
+pub fn is_container(value: &Value) -> bool {
+    matches!(value, &Value::List(_) | &Value::Atom(_) | &Value::Lambda(_) | &Value::Map(_) | &Value::Record(_))
+}
+
+//the statistics of the collector, they are shared by all interpreters
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct GcStats {
+    pub collections: usize,
+    pub freed_atoms: usize,
+    pub freed_lists: usize,
+    //the atoms that are alive
+    pub atoms: usize,
+    //the atoms and chunks that are checked by the next collection
+    pub candidates: usize
+}
+
+struct Collector {
+    candidates: Vec<Node>,
+    threshold: usize,
+    stats: GcStats
+}
+
+fn collector() -> MutexGuard<'static, Collector> {
+    static COLLECTOR: OnceLock<Mutex<Collector>> = OnceLock::new();
+    COLLECTOR.get_or_init(|| Mutex::new(Collector {
+        candidates: Vec::new(),
+        threshold: MIN_THRESHOLD,
+        stats: GcStats::default()
+    })).lock().unwrap_or_else(|x| x.into_inner())
+}
+
+pub fn track_atom(cell: Weak<AtomCell>) {
+    track(Node::Atom(cell));
+}
+
+pub fn track_chunk(chunk: Weak<Chunk>) {
+    track(Node::Chunk(chunk));
+}
+
+//adds a candidate and starts a collection if there are enough of them
+fn track(node: Node) {
+    let full = {
+        let mut collector = collector();
+        collector.candidates.push(node);
+        collector.candidates.len() >= collector.threshold
+    };
+    if full {
+        collect();
+    }
+}
+
+pub fn atom_created() {
+    ATOMS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn atom_dropped() {
+    ATOMS.fetch_sub(1, Ordering::Relaxed);
+}
+
+pub fn stats() -> GcStats {
+    let collector = collector();
+    GcStats {
+        atoms: ATOMS.load(Ordering::Relaxed),
+        candidates: collector.candidates.len(),
+        ..collector.stats
+    }
+}
+
+//counts an interpreter thread as running until the guard is dropped
+pub struct ThreadGuard;
+
+impl ThreadGuard {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> ThreadGuard {
+        THREADS.fetch_add(1, Ordering::SeqCst);
+        ThreadGuard
+    }
+}
+
+impl Drop for ThreadGuard {
+    fn drop(&mut self) {
+        THREADS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+//frees the cycles that are not used anymore and returns the number of atoms and chunks that were cleared
+//nothing is collected while other interpreter threads run or jobs are queued, because they could change the references
+//during the collection, the candidates are kept then, so the first collection after the jobs finished frees their cycles
+#[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference, clippy::single_match)]
+pub fn collect() -> usize {
+    if THREADS.load(Ordering::SeqCst) > 0 {
+        return 0;
+    }
+    let candidates = mem::take(&mut collector().candidates);
+    let candidates = candidates.into_iter().filter(|x| x.strong_count() > 0).collect::<Vec<_>>();
+    //the nodes that are reachable from the candidates with the addresses of their children
+    let mut graph: HashMap<usize, (Node, Vec<usize>)> = HashMap::new();
+    let mut pending = candidates.clone();
+    while let Some(node) = pending.pop() {
+        let address = node.address();
+        if graph.contains_key(&address) || node.strong_count() == 0 {
+            continue;
+        }
+        let children = match node.children() {
+            Some(children) => children,
+            None => {
+                //an atom is locked, the next collection tries again
+                collector().candidates.extend(candidates);
+                return 0;
+            }
+        };
+        graph.insert(address, (node, children.iter().map(|x| x.address()).collect()));
+        pending.extend(children);
+    }
+    let mut traced: HashMap<usize, usize> = HashMap::new();
+    for &(_, ref children) in graph.values() {
+        for child in children {
+            *traced.entry(*child).or_insert(0) += 1;
+        }
+    }
+    let mut pending = graph.iter()
+        .filter(|&(address, &(ref node, _))| node.strong_count() > traced.get(address).cloned().unwrap_or(0))
+        .map(|x| *x.0)
+        .collect::<Vec<_>>();
+    let mut used = HashSet::new();
+    while let Some(address) = pending.pop() {
+        if used.insert(address) {
+            pending.extend(graph[&address].1.iter().cloned());
+        }
+    }
+    //the garbage is kept alive until all of it is cleared, the values are dropped afterwards
+    let mut garbage = (Vec::new(), Vec::new());
+    let mut values = Vec::new();
+    for (_, &(ref node, _)) in graph.iter().filter(|x| !used.contains(x.0)) {
+        match node {
+            &Node::Atom(ref cell) => match cell.upgrade() {
+                Some(cell) => {
+                    values.push(mem::replace(&mut *cell.lock(), Value::Nil));
+                    garbage.0.push(cell);
+                },
+                None => {}
+            },
+            &Node::Chunk(ref chunk) => match chunk.upgrade() {
+                Some(chunk) => {
+                    // SAFETY: only garbage refers to the chunk and no other interpreter thread runs, see THREADS
+                    values.extend(unsafe { chunk.clear() });
+                    garbage.1.push(chunk);
+                },
+                None => {}
+            }
+        }
+    }
+    let (freed_atoms, freed_lists) = (garbage.0.len(), garbage.1.len());
+    drop(garbage);
+    drop(values);
+    let mut collector = collector();
+    let survivors = candidates.into_iter().filter(|x| x.strong_count() > 0 && used.contains(&x.address()));
+    collector.candidates.extend(survivors);
+    collector.threshold = MIN_THRESHOLD.max(collector.candidates.len() * 2);
+    collector.stats.collections += 1;
+    collector.stats.freed_atoms += freed_atoms;
+    collector.stats.freed_lists += freed_lists;
+    freed_atoms + freed_lists
+}
\ No newline at end of file
@@ -1,13 +1,24 @@
+use symbol::Symbol;
 use value::Value;
 use error::Error;
 use list::List;
+use list::resolve;
 use scope::Scope;
+use optimizer::optimize_list;
+use corelib::math;
+use gc;
+use gc::GcStats;
 
 use std::io::Read;
 use std::io::Result as IOResult;
 use std::fs::File;
 use stack::Stack;
+use debug::DebugHook;
+use profiler::Profiler;
+use trace::Tracer;
+use testrunner::TestCase;
 
+#[allow(clippy::redundant_static_lifetimes)]
 pub const STD_LIST: [&'static str; 1] = [
     "std/basics.ali",
 ];
@@ -15,10 +26,11 @@ pub const STD_LIST: [&'static str; 1] = [
 //TODO: change printlns to function return values
 
 pub struct Interpreter {
-    global: Scope
+    stack: Stack
 }
 
 impl Interpreter {
+    #[allow(clippy::useless_format)]
     pub fn load_std(&mut self) -> IOResult<()> {
         for elem in STD_LIST.iter() {
             self.load_script(format!("{}", elem))?;
@@ -27,11 +39,37 @@ impl Interpreter {
     }
 
     pub fn new_empty() -> Interpreter {
+        let mut global = Scope::new();
+        for (name, value) in math::constants() {
+            global.set_variable(Symbol::new(name), value);
+        }
+        Interpreter {
+            stack: Stack::from_scopes(vec!(global))
+        }
+    }
+
+    //an interpreter that starts with a copy of another interpreters global scope
+    pub fn from_global(global: Scope) -> Interpreter {
         Interpreter {
-            global: Scope::new()
+            stack: Stack::from_scopes(vec!(global))
         }
     }
 
+    pub fn global_scope(&self) -> &Scope {
+        self.stack.global().unwrap()
+    }
+
+    //frees the cycles of atoms and lists that are not used anymore, returns the number of freed values
+    //the heap is shared by all interpreters, see gc
+    pub fn collect_garbage(&mut self) -> usize {
+        gc::collect()
+    }
+
+    pub fn gc_stats(&self) -> GcStats {
+        gc::stats()
+    }
+
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Interpreter {
         let mut interpreter = Interpreter::new_empty();
         match interpreter.load_std() {
@@ -49,22 +87,71 @@ impl Interpreter {
         self.eval(list)
     }
 
+    //the list is optimized before it is evaluated, see optimizer
     pub fn eval(&mut self, list: List) -> Result<Value, Error> {
-        let mut stack = Stack::from_scopes(vec!(self.global.clone()));
-        let result = list.eval(&mut stack, None);
-        self.global = stack.into_first_scope().unwrap();
+        let result = optimize_list(&list).eval(&mut self.stack, None);
+        self.stack.truncate(1); //only the global scope survives
         result
     }
 
+    //evaluates a value the same way a function argument is evaluated
+    pub fn eval_value(&mut self, value: Value) -> Result<Value, Error> {
+        match value {
+            Value::List(list) => self.eval(list),
+            other => resolve(other, &mut self.stack, "eval")
+        }
+    }
+
+    //the tests that were registered by 'deftest'
+    pub fn take_tests(&mut self) -> Vec<TestCase> {
+        self.stack.take_tests()
+    }
+
+    pub fn set_debug_hook(&mut self, hook: Box<dyn DebugHook>) {
+        self.stack.set_debug_hook(Some(hook));
+    }
+
+    pub fn take_debug_hook(&mut self) -> Option<Box<dyn DebugHook>> {
+        self.stack.take_debug_hook()
+    }
+
+    pub fn set_profiler(&mut self, profiler: Profiler) {
+        self.stack.set_profiler(Some(profiler));
+    }
+
+    pub fn take_profiler(&mut self) -> Option<Profiler> {
+        self.stack.take_profiler()
+    }
+
+    pub fn set_tracer(&mut self, tracer: Tracer) {
+        self.stack.set_tracer(Some(tracer));
+    }
+
+    pub fn take_tracer(&mut self) -> Option<Tracer> {
+        self.stack.take_tracer()
+    }
+
     pub fn load_script(&mut self, path: String) -> IOResult<()> {
         let mut code = String::new();
         let mut file = File::open(path)?;
         let _ = file.read_to_string(&mut code)?;
         let mut line_buffer = String::new();
         let mut code_iter = code.chars();
+        let mut line = 1;
+        //inside a doc block only a bracket at the start of a line begins code, so that examples are not evaluated
+        let mut doc_block = false;
+        let mut line_start = true;
         loop {
-            match code_iter.next() {
-                Some('(') => {
+            let ch = code_iter.next();
+            let at_line_start = line_start;
+            line_start = ch == Some('\n');
+            match ch {
+                Some('@') if at_line_start => {
+                    doc_block = true;
+                },
+                Some('(') if !doc_block || at_line_start => {
+                    doc_block = false;
+                    let first_line = line;
                     let mut bracket_balance = 0; //TODO: make this a function and replace it in lib::list
                     loop {
                         match code_iter.next() {
@@ -74,6 +161,7 @@ impl Interpreter {
                             },
                             Some(')') => {
                                 if bracket_balance == 0 {
+                                    self.stack.set_location(Some((first_line, line)));
                                     match self.eval_string(line_buffer) {
                                         Ok(_) => {},
                                         Err(e) => println!("{}", e)
@@ -87,6 +175,9 @@ impl Interpreter {
                                 bracket_balance -= 1;
                             },
                             Some(ch) => {
+                                if ch == '\n' {
+                                    line += 1;
+                                }
                                 line_buffer.push(ch);
                             }
                             None => {
@@ -96,12 +187,16 @@ impl Interpreter {
                         };
                     }
                 },
+                Some('\n') => {
+                    line += 1;
+                },
                 Some(_) => {},
                 None => {
                     break;
                 }
             }
         }
+        self.stack.set_location(None);
         Ok(())
     }
 }
\ No newline at end of file
@@ -1,28 +1,223 @@
 use list::List;
+use list::resolve;
+use symbol::Symbol;
 use value::Value;
 use error::Error;
 use stack::Stack;
+use scope::Scope;
+use pattern::Pattern;
+use resolver::resolve_lambda;
+use optimizer::optimize_list;
+
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+enum Section {
+    Required,
+    Optional,
+    Rest,
+    Key
+}
+
+//the parameter list of a lambda, for example '(a (b c) &optional [c 1] & rest &key [d 2])
+//required parameters can be patterns that destructure the argument
+//optional and keyword parameters without a default value are nil
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct Params {
+    required: Vec<Pattern>,
+    optional: Vec<(Symbol, Value)>,
+    rest: Option<Symbol>,
+    keys: Vec<(Symbol, Value)>
+}
+
+impl Params {
+    //TODO: check uniqueness of the parameter
+    #[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference, clippy::single_match, clippy::useless_format)]
+    pub fn from_cells(cells: &[Value]) -> Result<Params, Error> {
+        let mut params = Params {
+            required: Vec::new(),
+            optional: Vec::new(),
+            rest: None,
+            keys: Vec::new()
+        };
+        let mut section = Section::Required;
+        for cell in cells {
+            let next_section = match cell {
+                &Value::Symbol(ref symbol) => match &symbol[..] {
+                    "&optional" => Some(Section::Optional),
+                    "&" | "&rest" => Some(Section::Rest),
+                    "&key" => Some(Section::Key),
+                    _ => None
+                },
+                _ => None
+            };
+            match next_section {
+                Some(next) => {
+                    if next <= section || (section == Section::Rest && params.rest.is_none()) {
+                        return Err(Error::new(format!("invalid parameter list: unexpected {}.", cell)));
+                    }
+                    section = next;
+                    continue;
+                },
+                None => {}
+            }
+            if section == Section::Required {
+                params.required.push(Pattern::from_value(cell)?);
+                continue;
+            }
+            let (name, default) = match cell {
+                &Value::Symbol(ref symbol) => (symbol.clone(), Value::Nil),
+                &Value::List(ref list) if list.cells().len() == 2 => {
+                    match list.cells().first().unwrap() {
+                        &Value::Symbol(ref symbol) => (symbol.clone(), list.cells().get(1).unwrap().clone()),
+                        _ => return Err(Error::new(format!("invalid parameter list: expected a symbol as parameter name, found {}.", cell)))
+                    }
+                },
+                _ => {
+                    return Err(Error::new(format!("only symbols can be used as function parameters.")));
+                }
+            };
+            match section {
+                Section::Required => unreachable!(),
+                Section::Optional => params.optional.push((name, default)),
+                Section::Rest => {
+                    if params.rest.is_some() {
+                        return Err(Error::new(format!("invalid parameter list: only one parameter can follow '&'.")));
+                    }
+                    params.rest = Some(name);
+                },
+                Section::Key => params.keys.push((name, default))
+            }
+        }
+        if section == Section::Rest && params.rest.is_none() {
+            return Err(Error::new(format!("invalid parameter list: expected a parameter after '&'.")));
+        }
+        Ok(params)
+    }
+
+    //the names in the order the parameters are bound
+    pub fn names(&self) -> Vec<Symbol> {
+        let mut names = self.required.iter().flat_map(|x| x.names()).collect::<Vec<_>>();
+        names.extend(self.optional.iter().map(|x| x.0.clone()));
+        names.extend(self.rest.iter().cloned());
+        names.extend(self.keys.iter().map(|x| x.0.clone()));
+        names
+    }
+
+    //the names of the parameters, if all of them are required symbols
+    #[allow(clippy::len_zero, clippy::needless_borrowed_reference)]
+    pub fn simple_names(&self) -> Option<Vec<Symbol>> {
+        if self.optional.len() > 0 || self.rest.is_some() || self.keys.len() > 0 {
+            return None;
+        }
+        self.required.iter().map(|x| match x {
+            &Pattern::Bind(ref name) => Some(name.clone()),
+            _ => None
+        }).collect()
+    }
+
+    //binds the arguments to the parameter names, default values are evaluated when they are needed
+    #[allow(clippy::len_zero, clippy::needless_borrowed_reference, clippy::single_match)]
+    pub fn bind(&self, args: Vec<Value>, stack: &mut Stack) -> Result<Vec<(Symbol, Value)>, Error> {
+        let mut positional = Vec::new();
+        let mut keywords = Vec::new();
+        let mut arg_iter = args.into_iter();
+        while let Some(arg) = arg_iter.next() {
+            match self.key_index(&arg) {
+                Some(index) => match arg_iter.next() {
+                    Some(value) => keywords.push((index, value)),
+                    None => return Err(Error::new(format!("'lambda': expected a value after the keyword {}.", arg)))
+                },
+                None if keywords.len() > 0 => {
+                    return Err(Error::new(format!("'lambda': expected a keyword, found {}.", arg)));
+                },
+                None => positional.push(arg)
+            }
+        }
+        let min_len = self.required.len();
+        let max_len = min_len + self.optional.len();
+        if positional.len() < min_len || (self.rest.is_none() && positional.len() > max_len) {
+            let expected = match (self.rest.is_some(), min_len == max_len) {
+                (true, _) => format!("at least {}", min_len),
+                (false, true) => format!("{}", min_len),
+                (false, false) => format!("{} to {}", min_len, max_len)
+            };
+            return Err(Error::new(format!("'lambda': expected {} parameters, found {}.", expected, positional.len())));
+        }
+        let mut bindings = Vec::new();
+        let mut positional_iter = positional.into_iter();
+        for pattern in &self.required {
+            match pattern.destructure(&positional_iter.next().unwrap()) {
+                Ok(pattern_bindings) => bindings.extend(pattern_bindings),
+                Err(err) => return Err(Error::new(format!("'lambda': {}", err.message())))
+            }
+        }
+        for &(ref name, ref default) in &self.optional {
+            let value = match positional_iter.next() {
+                Some(value) => value,
+                None => default_value(default, &bindings, stack)?
+            };
+            bindings.push((name.clone(), value));
+        }
+        match self.rest {
+            Some(ref name) => bindings.push((name.clone(), Value::List(List::from_cells(positional_iter.collect())))),
+            None => {}
+        }
+        for (i, &(ref name, ref default)) in self.keys.iter().enumerate() {
+            let value = match keywords.iter().rev().find(|x| x.0 == i) {
+                Some(&(_, ref value)) => value.clone(),
+                None => default_value(default, &bindings, stack)?
+            };
+            bindings.push((name.clone(), value));
+        }
+        Ok(bindings)
+    }
+
+    //the index of the keyword parameter the argument names, like :name for the parameter name
+    #[allow(clippy::needless_borrowed_reference)]
+    fn key_index(&self, arg: &Value) -> Option<usize> {
+        match arg {
+            &Value::Symbol(ref symbol) if symbol.starts_with(':') => {
+                self.keys.iter().position(|x| x.0 == symbol[1..])
+            },
+            _ => None
+        }
+    }
+}
+
+//evaluates a default value in a scope that contains the parameters bound so far
+#[allow(clippy::needless_borrowed_reference)]
+fn default_value(default: &Value, bindings: &[(Symbol, Value)], stack: &mut Stack) -> Result<Value, Error> {
+    let mut scope = Scope::new();
+    for &(ref name, ref value) in bindings {
+        scope.set_variable(name.clone(), value.clone());
+    }
+    stack.push(scope);
+    let result = resolve(default.clone(), stack, "lambda");
+    stack.pop();
+    result
+}
 
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct Lambda {
-    param_names: Vec<String>,
+    //shared, so that resolving a lambda by its name does not copy the parameters
+    params: Arc<Params>,
+    //the arguments that were given to 'partial', they precede the arguments of a call
+    bound: Vec<Value>,
     body: List
 }
 
 impl Lambda {
-    //TODO: check uniqueness of the parameter
-    pub fn new(param_names: Vec<String>, body: List) -> Lambda {
+    //the body is optimized and its variables are resolved, see optimizer and resolver
+    pub fn new(params: Params, body: List) -> Lambda {
         Lambda {
-            param_names: param_names,
-            body: body
+            body: resolve_lambda(&params, &optimize_list(&body)),
+            params: Arc::new(params),
+            bound: Vec::new()
         }
     }
 
-    pub fn param_count(&self) -> usize {
-        self.param_names.len()
-    }
-
-    pub fn eval_with_trace(&mut self, params: Vec<Value>, stack: &mut Stack, trace: String) -> Result<Value, Error> {
+    pub fn eval_with_trace(&self, params: Vec<Value>, stack: &mut Stack, trace: String) -> Result<Value, Error> {
         match self.eval(params, stack) {
             Ok(v) => Ok(v),
             Err(err) => {
@@ -31,64 +226,51 @@ impl Lambda {
         }
     }
 
-    pub fn substitute(&self, param: String, value: Value) -> Lambda {
+    //a lambda that calls this one with the given arguments before the ones it gets
+    pub fn partial(&self, args: Vec<Value>) -> Lambda {
         let mut retval = self.clone();
-        retval.body = Lambda::substitute_list(retval.body, param, value);
-        retval.param_names.remove(0);
-        return retval;
+        retval.bound.extend(args);
+        retval
     }
 
-    fn substitute_list(list: List, param: String, value: Value) -> List {
-        let mut new_cells = Vec::new();
-        for elem in list.into_cells() {
-            match elem {
-                Value::Symbol(p) => {
-                    if p == param {
-                        new_cells.push(value.clone());
-                    }
-                    else {
-                        new_cells.push(Value::Symbol(p));
-                    }
-                },
-                Value::List(inner_list) => {
-                    new_cells.push(Value::List(Lambda::substitute_list(inner_list, param.clone(), value.clone())));
-                }
-                e => {
-                    new_cells.push(e);
-                }
-            }
+    //the parameter names and the body, if the lambda only has required symbols as parameters and no bound arguments
+    pub fn simple_parts(&self) -> Option<(Vec<Symbol>, &List)> {
+        match (self.bound.len(), self.params.simple_names()) {
+            (0, Some(names)) => Some((names, &self.body)),
+            _ => None
         }
+    }
 
-        List::from_cells(new_cells)
+    //the values the lambda holds for the garbage collector, see gc
+    //the parameters are shared and not included, so the values of their defaults are never collected
+    pub fn values(&self) -> (&List, &[Value]) {
+        (&self.body, &self.bound)
     }
 
-    pub fn eval(&mut self, params: Vec<Value>, stack: &mut Stack) -> Result<Value, Error> {
-        let expected_len = self.param_names.len();
-        let found_len = params.len();
-        if expected_len < found_len || found_len == 0 {
-            return Err(Error::new(format!("'lambda': expected {} parameters, found {}.", expected_len, found_len)));
-        }
-        let param_vec = self.param_names.clone().into_iter().zip(params).collect::<Vec<(String, Value)>>();
-        if expected_len > found_len {
-            let mut lambda = self.clone();
-            for elem in param_vec {
-                lambda = lambda.substitute(elem.0, elem.1);
-            }
-            return Ok(Value::Lambda(lambda));
-        }
+    pub fn eval(&self, params: Vec<Value>, stack: &mut Stack) -> Result<Value, Error> {
+        let mut args = self.bound.clone();
+        args.extend(params);
+        let param_vec = self.params.bind(args, stack)?;
         match self.body.eval(stack, Some(param_vec)) {
             Ok(v) => Ok(v),
             err => err
         }
     }
 
+    #[allow(clippy::useless_format)]
     pub fn from_string(string: String) -> Result<Lambda, Error> {
-        let split_index = match string.find('|') {
+        let split_index = match head_end(&string) {
             Some(index) => index,
             None => return Err(Error::new(format!("lambda definition: expected second '|'.")))
         };
         let (head, body_str) = string.split_at(split_index);
-        let params = head.split_whitespace().map(|x| x.to_owned()).collect::<Vec<_>>();
+        let params = match List::from_string(head.to_owned()).and_then(|x| Params::from_cells(x.cells())) {
+            Ok(v) => v,
+            Err(mut err) => {
+                err = err.add_trace(format!("lambda definition"));
+                return Err(err);
+            }
+        };
         let body = match List::from_string(body_str[1..].to_owned()) { // 1.. to remove |
             Ok(v) => v,
             Err(mut err) => {
@@ -98,4 +280,18 @@ impl Lambda {
         };
         Ok(Lambda::new(params, body))
     }
+}
+
+//the index of the '|' that ends the parameters, a '|' inside of a default value is skipped
+fn head_end(string: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, ch) in string.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            '|' if depth == 0 => return Some(i),
+            _ => {}
+        }
+    }
+    None
 }
\ No newline at end of file
@@ -0,0 +1,327 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+
+use error::Error;
+use lambda::Lambda;
+use number;
+use number::Number;
+use stack::Stack;
+use value::Value;
+
+thread_local! {
+    //the addresses of the sequences whose elements this thread computes, to find sequences that need themselves
+    static COMPUTING: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+}
+
+//where the elements of a lazy sequence come from
+#[derive(Debug)]
+enum Source {
+    //the values of a list, starting at the index
+    Values(Vec<Value>, usize),
+    //numbers from next in steps until end [exclusive], without an end the range is infinite
+    Range(Value, Option<Value>, Value),
+    //the value and the results of applying the lambda to the previous element
+    Iterate(Lambda, Value),
+    //the value repeated a number of times or infinitely
+    Repeat(Value, Option<usize>),
+    //the values repeated infinitely, starting at the index
+    Cycle(Vec<Value>, usize),
+    Map(Lambda, LazySeq),
+    Filter(Lambda, LazySeq),
+    //the number of elements that are still taken
+    Take(usize, LazySeq),
+    //the number of elements that still have to be skipped
+    Drop(usize, LazySeq),
+    TakeWhile(Lambda, LazySeq),
+    //true while elements are still dropped
+    DropWhile(Lambda, LazySeq, bool)
+}
+
+//the elements that were computed and the source of the next ones, shared by the copies of a sequence
+#[derive(Debug)]
+struct Realized {
+    //the computed elements that a copy can still read, starting at the index offset
+    values: VecDeque<Value>,
+    offset: usize,
+    source: Source,
+    done: bool
+}
+
+//a sequence whose elements are computed when they are needed, so it can be infinite
+//the sequence is a value like any other, a copy starts at the same element
+//the copies share the computed elements, so every element is computed only once
+#[derive(Debug, Clone)]
+pub struct LazySeq {
+    realized: Arc<Mutex<Realized>>,
+    //the index of the next element of this copy
+    index: usize
+}
+
+impl LazySeq {
+    #[allow(clippy::redundant_field_names)]
+    fn new(source: Source) -> LazySeq {
+        LazySeq {
+            realized: Arc::new(Mutex::new(Realized {
+                values: VecDeque::new(),
+                offset: 0,
+                source: source,
+                done: false
+            })),
+            index: 0
+        }
+    }
+
+    //the step must not be zero
+    pub fn range(start: Value, end: Option<Value>, step: Value) -> LazySeq {
+        LazySeq::new(Source::Range(start, end, step))
+    }
+
+    pub fn from_values(values: Vec<Value>) -> LazySeq {
+        LazySeq::new(Source::Values(values, 0))
+    }
+
+    pub fn iterate(lambda: Lambda, start: Value) -> LazySeq {
+        LazySeq::new(Source::Iterate(lambda, start))
+    }
+
+    pub fn repeat(value: Value, times: Option<usize>) -> LazySeq {
+        LazySeq::new(Source::Repeat(value, times))
+    }
+
+    pub fn cycle(values: Vec<Value>) -> LazySeq {
+        LazySeq::new(Source::Cycle(values, 0))
+    }
+
+    pub fn map(self, lambda: Lambda) -> LazySeq {
+        LazySeq::new(Source::Map(lambda, self))
+    }
+
+    pub fn filter(self, lambda: Lambda) -> LazySeq {
+        LazySeq::new(Source::Filter(lambda, self))
+    }
+
+    pub fn take(self, count: usize) -> LazySeq {
+        LazySeq::new(Source::Take(count, self))
+    }
+
+    pub fn drop(self, count: usize) -> LazySeq {
+        LazySeq::new(Source::Drop(count, self))
+    }
+
+    pub fn take_while(self, lambda: Lambda) -> LazySeq {
+        LazySeq::new(Source::TakeWhile(lambda, self))
+    }
+
+    pub fn drop_while(self, lambda: Lambda) -> LazySeq {
+        LazySeq::new(Source::DropWhile(lambda, self, true))
+    }
+
+    //the next element, None if the sequence has ended
+    //an element is computed once and kept for the other copies, a copy without others keeps nothing
+    #[allow(clippy::useless_format)]
+    pub fn next(&mut self, stack: &mut Stack) -> Result<Option<Value>, Error> {
+        let address = Arc::as_ptr(&self.realized) as usize;
+        if COMPUTING.with(|x| x.borrow().contains(&address)) {
+            return Err(Error::new(format!("a lazy sequence needs its own elements to compute them.")));
+        }
+        let alone = Arc::strong_count(&self.realized) == 1;
+        let mut realized = self.lock();
+        if alone {
+            let read = self.index - realized.offset;
+            realized.values.drain(..read);
+            realized.offset = self.index;
+        }
+        while realized.offset + realized.values.len() <= self.index {
+            if realized.done {
+                return Ok(None);
+            }
+            COMPUTING.with(|x| x.borrow_mut().push(address));
+            let next = realized.source.next(stack);
+            COMPUTING.with(|x| x.borrow_mut().pop());
+            match next? {
+                Some(value) => realized.values.push_back(value),
+                None => realized.done = true
+            }
+        }
+        let value = match alone {
+            true => {
+                realized.offset += 1;
+                realized.values.pop_front()
+            },
+            false => realized.values.get(self.index - realized.offset).cloned()
+        };
+        drop(realized);
+        self.index += 1;
+        Ok(value)
+    }
+
+    //a panic in another thread does not make the sequence unusable
+    fn lock(&self) -> MutexGuard<'_, Realized> {
+        self.realized.lock().unwrap_or_else(|x| x.into_inner())
+    }
+
+    //the element at the index, only the elements up to it are computed
+    pub fn nth(mut self, index: usize, stack: &mut Stack) -> Result<Option<Value>, Error> {
+        for _ in 0..index {
+            if self.next(stack)?.is_none() {
+                return Ok(None);
+            }
+        }
+        self.next(stack)
+    }
+
+    //computes all elements, an error for sequences that are infinite for sure
+    #[allow(clippy::useless_format)]
+    pub fn force(mut self, stack: &mut Stack) -> Result<Vec<Value>, Error> {
+        if self.lock().source.is_infinite() {
+            return Err(Error::new(format!("can not compute all elements of an infinite lazy sequence.")));
+        }
+        let mut values = Vec::new();
+        while let Some(value) = self.next(stack)? {
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+//two sequences are only equal if they are copies at the same element
+impl PartialEq for LazySeq {
+    fn eq(&self, other: &LazySeq) -> bool {
+        Arc::ptr_eq(&self.realized, &other.realized) && self.index == other.index
+    }
+}
+
+impl PartialOrd for LazySeq {
+    fn partial_cmp(&self, other: &LazySeq) -> Option<Ordering> {
+        match self == other {
+            true => Some(Ordering::Equal),
+            false => None
+        }
+    }
+}
+
+impl Source {
+    //true if the source never ends, a filter of an infinite sequence counts as infinite,
+    //because computing all of its elements does not return either
+    #[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+    fn is_infinite(&self) -> bool {
+        match self {
+            &Source::Range(_, None, _) | &Source::Iterate(_, _) | &Source::Repeat(_, None) => true,
+            &Source::Range(_, Some(ref end), ref step) => match (Number::from_value(end), Number::from_value(step)) {
+                (Some(Number::Float(end)), Some(step)) => {
+                    let ascending = number::compare(&step, &Number::Integer(0)) == Some(Ordering::Greater);
+                    end.is_infinite() && (end > 0.0) == ascending
+                },
+                _ => false
+            },
+            &Source::Cycle(ref values, _) => !values.is_empty(),
+            &Source::Map(_, ref inner) | &Source::Filter(_, ref inner) | &Source::Drop(_, ref inner) | &Source::DropWhile(_, ref inner, _) => {
+                inner.lock().source.is_infinite()
+            },
+            &Source::Repeat(_, Some(_)) | &Source::Values(_, _) | &Source::Take(_, _) | &Source::TakeWhile(_, _) => false
+        }
+    }
+
+    //computes the next element, None if the source has ended
+    #[allow(clippy::useless_format)]
+    fn next(&mut self, stack: &mut Stack) -> Result<Option<Value>, Error> {
+        match *self {
+            Source::Values(ref values, ref mut index) => {
+                *index += 1;
+                Ok(values.get(*index - 1).cloned())
+            },
+            Source::Range(ref mut next, ref end, ref step) => {
+                let (current, step) = (Number::from_value(next).unwrap(), Number::from_value(step).unwrap());
+                let ascending = number::compare(&step, &Number::Integer(0)) == Some(Ordering::Greater);
+                match end.as_ref().map(|x| number::compare(&current, &Number::from_value(x).unwrap())) {
+                    Some(Some(Ordering::Less)) if ascending => {},
+                    Some(Some(Ordering::Greater)) if !ascending => {},
+                    Some(_) => return Ok(None),
+                    None => {}
+                }
+                let retval = next.clone();
+                *next = number::add(current, step).into_value();
+                Ok(Some(retval))
+            },
+            Source::Iterate(ref lambda, ref mut next) => {
+                let following = lambda.eval_with_trace(vec!(next.clone()), stack, format!("iterate"))?;
+                Ok(Some(::std::mem::replace(next, following)))
+            },
+            Source::Repeat(ref value, ref mut times) => {
+                match *times {
+                    Some(0) => return Ok(None),
+                    Some(ref mut remaining) => *remaining -= 1,
+                    None => {}
+                }
+                Ok(Some(value.clone()))
+            },
+            Source::Cycle(ref values, ref mut index) => {
+                if values.is_empty() {
+                    return Ok(None);
+                }
+                let retval = values[*index].clone();
+                *index = (*index + 1) % values.len();
+                Ok(Some(retval))
+            },
+            Source::Map(ref lambda, ref mut inner) => {
+                match inner.next(stack)? {
+                    Some(value) => Ok(Some(lambda.eval_with_trace(vec!(value), stack, format!("map"))?)),
+                    None => Ok(None)
+                }
+            },
+            Source::Filter(ref lambda, ref mut inner) => {
+                while let Some(value) = inner.next(stack)? {
+                    if predicate(lambda, &value, stack, "filter")? {
+                        return Ok(Some(value));
+                    }
+                }
+                Ok(None)
+            },
+            Source::Take(ref mut remaining, ref mut inner) => {
+                if *remaining == 0 {
+                    return Ok(None);
+                }
+                *remaining -= 1;
+                inner.next(stack)
+            },
+            Source::Drop(ref mut remaining, ref mut inner) => {
+                while *remaining > 0 {
+                    *remaining -= 1;
+                    if inner.next(stack)?.is_none() {
+                        return Ok(None);
+                    }
+                }
+                inner.next(stack)
+            },
+            Source::TakeWhile(ref lambda, ref mut inner) => {
+                match inner.next(stack)? {
+                    Some(value) => match predicate(lambda, &value, stack, "take-while")? {
+                        true => Ok(Some(value)),
+                        false => Ok(None)
+                    },
+                    None => Ok(None)
+                }
+            },
+            Source::DropWhile(ref lambda, ref mut inner, ref mut dropping) => {
+                while let Some(value) = inner.next(stack)? {
+                    if !*dropping || !predicate(lambda, &value, stack, "drop-while")? {
+                        *dropping = false;
+                        return Ok(Some(value));
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+}
+
+fn predicate(lambda: &Lambda, value: &Value, stack: &mut Stack, fn_name: &'static str) -> Result<bool, Error> {
+    match lambda.eval_with_trace(vec!(value.clone()), stack, fn_name.to_owned())? {
+        Value::Boolean(boolean) => Ok(boolean),
+        other => Err(Error::new_with_origin(fn_name, format!("expected the lambda to return a boolean, found {}.", other.type_str())))
+    }
+}
\ No newline at end of file
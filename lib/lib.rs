@@ -1,12 +1,34 @@
+extern crate num_bigint;
+extern crate num_integer;
+extern crate num_rational;
+extern crate num_traits;
+
+pub mod atom;
 pub mod corelib;
+pub mod debug;
 pub mod error;
+pub mod formatter;
 pub mod functions;
+pub mod future;
+pub mod gc;
 pub mod interpreter;
 pub mod lambda;
+pub mod lazy;
 pub mod list;
+pub mod map;
+pub mod number;
+pub mod optimizer;
+pub mod pattern;
+pub mod pool;
+pub mod profiler;
+pub mod record;
+pub mod resolver;
 pub mod scope;
+pub mod symbol;
 pub mod value;
 pub mod stack;
+pub mod testrunner;
+pub mod trace;
 
 pub type FLOAT = f64;
 pub type INT = i64;
\ No newline at end of file
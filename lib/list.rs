@@ -1,6 +1,25 @@
+use std::cell::UnsafeCell;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
+use std::mem;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::slice;
+use std::sync::Arc;
+use std::sync::Weak;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering as AtomicOrdering;
+use std::sync::atomic::fence;
+
 use error::Error;
+use symbol::Symbol;
 use value::Value;
 use functions;
+use gc;
 use scope::Scope;
 use lambda::Lambda;
 use stack::Stack;
@@ -11,36 +30,201 @@ enum Quoted {
     Backquote
 }
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+//the storage of lists, shared by a list and the lists that are created from it by tail, init or cons
+//the slots from front to the end are initialized and never change, a list that starts at front can grow
+//by claiming the slot before it, so cons on the newest list of a chunk does not copy the elements
+pub struct Chunk {
+    slots: Box<[UnsafeCell<MaybeUninit<Value>>]>,
+    //the first claimed slot
+    front: AtomicUsize,
+    //the first written slot, it is front again once the claimed slot was written
+    written: AtomicUsize,
+    //false if the values can not refer to other chunks, see List::cons
+    nested: AtomicBool,
+    //true once the garbage collector knows the chunk, see gc
+    tracked: AtomicBool
+}
+
+// SAFETY: a free slot is written once by the thread that claimed it with the compare_exchange on front, and it is
+// only read through lists that were created afterwards or after the Release store of written showed that it was written.
+// an initialized slot is only changed by into_cells, when no other list refers to the chunk, and by clear
+unsafe impl Sync for Chunk {}
+
+impl Chunk {
+    //the values after a number of free slots
+    fn new(values: Vec<Value>, free: usize) -> Chunk {
+        let nested = values.iter().any(|x| !is_plain(x));
+        let mut slots = Vec::with_capacity(free + values.len());
+        slots.extend((0..free).map(|_| UnsafeCell::new(MaybeUninit::uninit())));
+        slots.extend(values.into_iter().map(|x| UnsafeCell::new(MaybeUninit::new(x))));
+        Chunk {
+            slots: slots.into_boxed_slice(),
+            front: AtomicUsize::new(free),
+            written: AtomicUsize::new(free),
+            nested: AtomicBool::new(nested),
+            tracked: AtomicBool::new(false)
+        }
+    }
+
+    //the written values, including the ones of other lists that share the chunk, for the garbage collector
+    //a slot that another thread claimed but did not write yet is not part of them, so cons can run at the same time
+    pub(crate) fn values(&self) -> &[Value] {
+        let written = self.written.load(AtomicOrdering::Acquire);
+        // SAFETY: the slots from written on were initialized before written was stored with Release,
+        // UnsafeCell and MaybeUninit have the layout of the value, and &self keeps the chunk alive
+        unsafe {
+            slice::from_raw_parts(self.slots.as_ptr().add(written) as *const Value, self.slots.len() - written)
+        }
+    }
+
+    //replaces the values with nil and returns them, the garbage collector calls it for chunks that no list can reach
+    /// # Safety
+    /// only values that are garbage may refer to the chunk and no other thread may read its values while they
+    /// are replaced, gc::collect ensures both, because it only runs while no other interpreter thread does
+    pub(crate) unsafe fn clear(&self) -> Vec<Value> {
+        let written = self.written.load(AtomicOrdering::Acquire);
+        // SAFETY: the slots from written on are initialized, see values, and nothing reads them, see above
+        self.slots[written..].iter().map(|x| mem::replace((*x.get()).assume_init_mut(), Value::Nil)).collect()
+    }
+
+    //claims the slot before index and writes the value to it, gives the value back if the slot is already used
+    fn prepend(&self, index: usize, value: Value) -> Option<Value> {
+        if index == 0 || self.front.compare_exchange(index, index - 1, AtomicOrdering::AcqRel, AtomicOrdering::Acquire).is_err() {
+            return Some(value);
+        }
+        if !is_plain(&value) {
+            self.nested.store(true, AtomicOrdering::Relaxed);
+        }
+        // SAFETY: the compare_exchange gave this thread the only claim on the slot, which is uninitialized because
+        // slots before front are never written, and no list can read the slot before the one returned by cons
+        unsafe {
+            (*self.slots[index - 1].get()).write(value);
+        }
+        //only the list that starts at index can claim the slot, so the slots are written one after another
+        self.written.store(index - 1, AtomicOrdering::Release);
+        None
+    }
+}
+
+impl Drop for Chunk {
+    fn drop(&mut self) {
+        let front = *self.front.get_mut();
+        for slot in self.slots[front..].iter_mut() {
+            // SAFETY: every claimed slot is written right after the claim, so the slots from front on are initialized,
+            // and they are dropped once, because nothing else can use the chunk anymore
+            unsafe {
+                slot.get_mut().assume_init_drop();
+            }
+        }
+    }
+}
+
+//a list is a range of a shared chunk, so cloning a list, tail and init do not copy the elements
+//and indexing stays O(1)
+#[derive(Clone)]
 pub struct List {
-    cells: Vec<Value>
+    chunk: Arc<Chunk>,
+    start: usize,
+    end: usize
 }
 
 impl List { 
     pub fn empty() -> List {
-        List {
-            cells: Vec::new()
-        }
+        List::from_cells(Vec::new())
     }
     
-    pub fn cells(&self) -> &Vec<Value> {
-        &self.cells
-    }
-
-    pub fn cells_mut(&mut self) -> &mut Vec<Value> {
-        &mut self.cells
+    pub fn cells(&self) -> &[Value] {
+        // SAFETY: the slots of the range were written before the list was created, UnsafeCell and MaybeUninit have
+        // the layout of the value, and the values do not change while the list exists, see into_cells and clear
+        unsafe {
+            slice::from_raw_parts(self.chunk.slots.as_ptr().add(self.start) as *const Value, self.end - self.start)
+        }
     }
 
+    //moves the elements out of the chunk if no other list uses it, otherwise they are cloned
+    //the weak reference of the garbage collector does not count, see below
     pub fn into_cells(self) -> Vec<Value> {
-        self.cells
+        if Arc::strong_count(&self.chunk) > 1 {
+            return self.cells().to_vec();
+        }
+        //the other lists of the chunk were dropped, their reads happened before the elements are replaced
+        fence(AtomicOrdering::Acquire);
+        // SAFETY: no other list refers to the chunk, so no other thread can read the slots of the range or claim one.
+        // the garbage collector only upgrades its weak reference while no other interpreter thread runs, see gc::collect,
+        // and it does not run during this call on this thread. the slots stay initialized, they are replaced with nil
+        self.chunk.slots[self.start..self.end].iter().map(|x| unsafe {
+            mem::replace((*x.get()).assume_init_mut(), Value::Nil)
+        }).collect()
     }
 
     pub fn from_cells(cells: Vec<Value>) -> List {
+        let len = cells.len();
         List {
-            cells: cells
+            chunk: Arc::new(Chunk::new(cells, 0)),
+            start: 0,
+            end: len
         }
     }
 
+    //the list with the value as first element, only copies the elements if another list already grew from this one
+    //the copy leaves as much space at the front as it has elements, so repeated cons is amortized O(1)
+    //a value that can reach the chunk is never written into it, because the chunk would then keep itself alive
+    pub fn cons(self, value: Value) -> List {
+        let plain = is_plain(&value);
+        let value = match plain || !reaches(&value, &self.chunk) {
+            true => self.chunk.prepend(self.start, value),
+            false => Some(value)
+        };
+        let value = match value {
+            Some(value) => value,
+            None => {
+                //two threads that cons onto lists which reach each other can still make a cycle, the collector frees it
+                if !plain && !self.chunk.tracked.swap(true, AtomicOrdering::Relaxed) {
+                    gc::track_chunk(Arc::downgrade(&self.chunk));
+                }
+                return List {
+                    start: self.start - 1,
+                    ..self
+                };
+            }
+        };
+        let mut cells = Vec::with_capacity(self.end - self.start + 1);
+        cells.push(value);
+        cells.extend_from_slice(self.cells());
+        let (len, free) = (cells.len(), cells.len().max(4));
+        List {
+            chunk: Arc::new(Chunk::new(cells, free)),
+            start: free,
+            end: free + len
+        }
+    }
+
+    //the storage of the list for the garbage collector, see gc
+    pub fn chunk(&self) -> Weak<Chunk> {
+        Arc::downgrade(&self.chunk)
+    }
+
+    //the elements from start to end [exclusive], shares the chunk
+    pub fn sublist(&self, start: usize, end: usize) -> List {
+        assert!(start <= end && end <= self.end - self.start);
+        List {
+            chunk: self.chunk.clone(),
+            start: self.start + start,
+            end: self.start + end
+        }
+    }
+
+    //all elements except for the first one
+    pub fn tail(&self) -> List {
+        self.sublist(1.min(self.cells().len()), self.cells().len())
+    }
+
+    //all elements except for the last one
+    pub fn init(&self) -> List {
+        self.sublist(0, self.cells().len().saturating_sub(1))
+    }
+
+    #[allow(clippy::len_zero, clippy::useless_format, clippy::while_let_loop)]
     pub fn from_string(code: String) -> Result<List, Error> {
         let mut cells = Vec::new();
         let mut buffer = String::new();
@@ -155,9 +339,7 @@ impl List {
                         }
                     }
                 }
-                let mut inner_cells = Vec::new();
-                inner_cells.push(Value::Symbol(format!("quote")));
-                inner_cells.push(Value::List(List::from_cells(string)));
+                let inner_cells = vec!(Value::Symbol(Symbol::new("quote")), Value::List(List::from_cells(string)));
                 cells.push(Value::List(List::from_cells(inner_cells)));
             }
             else if ch == '\'' {
@@ -197,13 +379,15 @@ impl List {
         if buffer.len() > 0 {
             push_to_cells(&mut cells, buffer, &mut quoted)?;
         }
-        Ok(List {
-            cells: cells
-        })
+        Ok(List::from_cells(cells))
     }
       
-    pub fn eval(&self, stack: &mut Stack, maybe_params: Option<Vec<(String, Value)>>) -> Result<Value, Error> {
+    #[allow(clippy::len_zero)]
+    pub fn eval(&self, stack: &mut Stack, maybe_params: Option<Vec<(Symbol, Value)>>) -> Result<Value, Error> {
         match maybe_params {
+            Some(ref params) if params.len() == 0 => {
+                stack.enter();
+            },
             Some(params) => {
                 let mut scope = Scope::new(); //create a new scope with the given parameters
                 for param in params {
@@ -212,28 +396,46 @@ impl List {
                 stack.push(scope);
             },
             None => {
-                stack.push(Scope::new()); //create a new empty scope for this function
+                stack.enter(); //a list without parameters only gets a scope if 'set' defines a variable in it
+            }
+        }
+        match stack.debug_before_eval(self) {
+            Ok(_) => {},
+            Err(err) => {
+                stack.pop();
+                return Err(err);
+            }
+        }
+        let mut result = self.eval_cells(stack);
+        match stack.debug_after_eval(self, &result) {
+            Ok(_) => {},
+            Err(err) => {
+                result = Err(err);
             }
         }
-        let mut retval = Value::Nil;
+        stack.pop(); //remove the scope of this function
+        result
+    }
+
+    //evaluates the list inside of the scope that was pushed by eval
+    #[allow(clippy::needless_borrow, clippy::needless_borrowed_reference)]
+    fn eval_cells(&self, stack: &mut Stack) -> Result<Value, Error> {
         let cell_count = self.cells().len();
         if cell_count == 0 {
-            stack.pop();
-            return Ok(retval)
+            return Ok(Value::Nil)
         }
         match functions::eval(&self, stack)? {
             Some(value) => { //the function has a result without an error
-                retval = value;
+                Ok(value)
             }, 
             None => { //there is no builtin function with that name, look for lambdas on the stack or execute the list
-                let mut cell_iter = self.cells.iter();
-                let name = match cell_iter.next().unwrap() {
-                    &Value::Symbol(ref name) => name,
+                let mut cell_iter = self.cells().iter();
+                let (name, function) = match cell_iter.next().unwrap() {
+                    &Value::Symbol(ref name) => (name, stack.resolve_variable(name)?),
+                    &Value::Variable(ref variable) => (variable.symbol(), stack.lookup(variable)?),
                     &Value::List(ref list) => {
                         if cell_count == 1 {
-                            let temp = list.clone().eval(stack, None);
-                            stack.pop();
-                            return temp;
+                            return list.clone().eval(stack, None);
                         }
                         else { 
                             //evaluate the inner list, append the following items, and evaluate that list
@@ -246,52 +448,109 @@ impl List {
                             for elem in cell_iter { //append remaining
                                 temp_cells.push(elem.clone());
                             }
-                            let temp = List::from_cells(temp_cells).eval(stack, None);
-                            stack.pop();
-                            return temp;
+                            return List::from_cells(temp_cells).eval(stack, None);
                         }
                     },
                     &Value::Lambda(ref lambda) => {
-                        stack.pop();
                         if cell_count == 1 {
                             return Ok(Value::Lambda(lambda.clone()));
                         }
                         else {
-                            return Err(Error::new(format!("the lambda is not the only element of the list.")));
+                            //a lambda that is the result of an expression, like in ((partial f 1) 2)
+                            let mut params = Vec::new();
+                            for elem in cell_iter {
+                                params.push(resolve(elem.clone(), stack, "lambda")?);
+                            }
+                            return lambda.eval(params, stack);
                         }
                     },
                     value => {
-                        stack.pop();
                         return Err(Error::new(format!("expected function name as first list item, found {}.", value.type_str())))
                     }
                 };
-                let mut lambda = match stack.resolve_variable(name)? {
+                let lambda = match function {
                     Value::Lambda(lambda) => lambda,
                     _ => {
-                        stack.pop();
                         return Err(Error::new(format!("unknown function '{}'.", name)))
                     }
                 };
-                let param_count = self.cells.len() - 1;
-                if param_count > lambda.param_count() || (lambda.param_count() != 0 && param_count == 0) {
-                    stack.pop();
-                    return Err(Error::new(format!("'{}': expected {} function parameters, found {}.", name, lambda.param_count(), param_count)));
-                }
                 let mut params = Vec::new();
-                for i in 1..self.cells.len() {
+                for i in 1..self.cells().len() {
                     let name_str = name.clone();
-                    let param = resolve(self.cells.get(i).unwrap().clone(), stack, &name_str[..])?;
+                    let param = resolve(self.cells().get(i).unwrap().clone(), stack, &name_str[..])?;
                     params.push(param);
                 }
-                retval = match lambda.eval(params, stack) {
-                    Ok(v) => v,
-                    err => return err
-                }; 
+                stack.profile_enter(name);
+                let traced = stack.trace_enter(name, &params, true);
+                let result = lambda.eval(params, stack);
+                stack.trace_leave(traced, name, &result);
+                stack.profile_leave();
+                result
             }
+        }
+    }
+}
+
+impl PartialEq for List {
+    fn eq(&self, other: &List) -> bool {
+        self.cells() == other.cells()
+    }
+}
+
+impl PartialOrd for List {
+    fn partial_cmp(&self, other: &List) -> Option<Ordering> {
+        self.cells().partial_cmp(other.cells())
+    }
+}
+
+impl Debug for List {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.debug_struct("List").field("cells", &self.cells()).finish()
+    }
+}
+
+//true if the value can not refer to a chunk
+fn is_plain(value: &Value) -> bool {
+    !matches!(value, &Value::List(_) | &Value::Lambda(_) | &Value::Map(_) | &Value::Record(_) |
+        &Value::Atom(_) | &Value::Future(_) | &Value::LazySeq(_))
+}
+
+//true if the value refers to the chunk through lists, lambdas, maps and records, or if it contains an atom,
+//a future or a lazy sequence, whose values can still change and refer to the chunk later
+#[allow(clippy::needless_borrowed_reference)]
+fn reaches(value: &Value, chunk: &Chunk) -> bool {
+    let mut pending = vec!(value);
+    let mut visited = HashSet::new();
+    while let Some(value) = pending.pop() {
+        let other = match value {
+            &Value::List(ref list) => &list.chunk,
+            &Value::Lambda(ref lambda) => {
+                let (body, bound) = lambda.values();
+                pending.extend(bound);
+                &body.chunk
+            },
+            &Value::Map(ref map) => {
+                for &(ref key, ref value) in map.entries() {
+                    pending.push(key);
+                    pending.push(value);
+                }
+                continue;
+            },
+            &Value::Record(ref record) => {
+                pending.extend(record.fields().iter().map(|x| &x.1));
+                continue;
+            },
+            &Value::Atom(_) | &Value::Future(_) | &Value::LazySeq(_) => return true,
+            _ => continue
         };
-        stack.pop(); //remove the scope of this function
-        Ok(retval)
+        if ptr::eq(&**other, chunk) {
+            return true;
+        }
+        if other.nested.load(AtomicOrdering::Relaxed) && visited.insert(&**other as *const Chunk) {
+            pending.extend(other.values());
+        }
     }
+    false
 }
 
 //helper function to tokenize a list string
@@ -312,7 +571,17 @@ fn push_to_cells(list: &mut Vec<Value>, buffer: String, quoted: &mut Quoted) ->
     Ok(())
 }
 
+//true for the symbol quote, which the parser puts in front of '(...)
+#[allow(clippy::needless_borrowed_reference)]
+pub fn is_quote(value: &Value) -> bool {
+    match value {
+        &Value::Symbol(ref symbol) => symbol == "quote",
+        _ => false
+    }
+}
+
 //resolves the parameters a function gets
+#[allow(clippy::needless_return)]
 pub fn resolve(val: Value, stack: &mut Stack, fn_name: &str) -> Result<Value, Error> {
     match val {
         Value::List(list) => {
@@ -326,8 +595,13 @@ pub fn resolve(val: Value, stack: &mut Stack, fn_name: &str) -> Result<Value, Er
             }
         },
         Value::Symbol(symbol) => {
+            //keywords like :name evaluate to themselves
+            if symbol.starts_with(':') && symbol.len() > 1 {
+                return Ok(Value::Symbol(symbol));
+            }
             stack.resolve_variable(&symbol)
         },
+        Value::Variable(variable) => stack.lookup(&variable),
         Value::Nil => {
             return Ok(Value::List(List::empty()));
         }
@@ -0,0 +1,64 @@
+use std::cmp::Ordering;
+
+use value::Value;
+
+//an association of keys to values that keeps the order in which the keys were added
+//two maps are equal if they contain the same entries, regardless of their order
+#[derive(Debug, Clone)]
+pub struct Map {
+    entries: Vec<(Value, Value)>
+}
+
+impl Map {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Map {
+        Map {
+            entries: Vec::new()
+        }
+    }
+
+    pub fn entries(&self) -> &Vec<(Value, Value)> {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &Value) -> Option<&Value> {
+        self.entries.iter().find(|x| x.0 == *key).map(|x| &x.1)
+    }
+
+    //replaces the value of an existing key or appends the entry
+    pub fn insert(&mut self, key: Value, value: Value) {
+        match self.entries.iter_mut().find(|x| x.0 == key) {
+            Some(entry) => entry.1 = value,
+            None => self.entries.push((key, value))
+        }
+    }
+
+    pub fn remove(&mut self, key: &Value) {
+        self.entries.retain(|x| x.0 != *key);
+    }
+}
+
+impl PartialEq for Map {
+    fn eq(&self, other: &Map) -> bool {
+        self.len() == other.len() && self.entries.iter().all(|x| other.get(&x.0) == Some(&x.1))
+    }
+}
+
+impl PartialOrd for Map {
+    fn partial_cmp(&self, other: &Map) -> Option<Ordering> {
+        if self == other {
+            Some(Ordering::Equal)
+        }
+        else {
+            self.entries.partial_cmp(&other.entries)
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,343 @@
+use std::cmp::Ordering;
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_integer::Integer;
+use num_traits::{
+    Signed,
+    ToPrimitive,
+    Zero
+};
+
+use ::FLOAT;
+use ::INT;
+use value::Value;
+
+//a numeric value, two numbers are brought to the more general type of both before an operation
+#[derive(Debug, Clone)]
+pub enum Number {
+    Integer(INT),
+    BigInt(BigInt),
+    Ratio(BigRational),
+    Float(FLOAT)
+}
+
+impl Number {
+    #[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+    pub fn from_value(value: &Value) -> Option<Number> {
+        match value {
+            &Value::Integer(i) => Some(Number::Integer(i)),
+            &Value::BigInt(ref b) => Some(Number::BigInt(b.clone())),
+            &Value::Ratio(ref r) => Some(Number::Ratio(r.clone())),
+            &Value::Float(f) => Some(Number::Float(f)),
+            _ => None
+        }
+    }
+
+    //big integers that fit into an INT and ratios with a denominator of one are demoted
+    pub fn into_value(self) -> Value {
+        match self {
+            Number::Integer(i) => Value::Integer(i),
+            Number::BigInt(b) => big_to_value(b),
+            Number::Ratio(r) => ratio_to_value(r),
+            Number::Float(f) => Value::Float(f)
+        }
+    }
+
+    #[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+    pub fn to_float(&self) -> FLOAT {
+        match self {
+            &Number::Integer(i) => i as FLOAT,
+            &Number::BigInt(ref b) => b.to_f64().unwrap_or(FLOAT::NAN),
+            &Number::Ratio(ref r) => r.to_f64().unwrap_or(FLOAT::NAN),
+            &Number::Float(f) => f
+        }
+    }
+
+    #[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+    fn to_big(&self) -> BigInt {
+        match self {
+            &Number::Integer(i) => BigInt::from(i),
+            &Number::BigInt(ref b) => b.clone(),
+            _ => unreachable!()
+        }
+    }
+
+    //the exact value of the number, None for infinite floats and nan
+    #[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+    pub fn to_ratio(&self) -> Option<BigRational> {
+        match self {
+            &Number::Integer(i) => Some(BigRational::from_integer(BigInt::from(i))),
+            &Number::BigInt(ref b) => Some(BigRational::from_integer(b.clone())),
+            &Number::Ratio(ref r) => Some(r.clone()),
+            &Number::Float(f) => BigRational::from_float(f)
+        }
+    }
+
+    //the value as an integer of any size, None for ratios and floats
+    #[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+    pub fn to_integer(&self) -> Option<BigInt> {
+        match self {
+            &Number::Integer(i) => Some(BigInt::from(i)),
+            &Number::BigInt(ref b) => Some(b.clone()),
+            _ => None
+        }
+    }
+
+    pub fn is_exact(&self) -> bool {
+        !matches!(self, &Number::Float(_))
+    }
+
+    #[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+    pub fn is_zero(&self) -> bool {
+        match self {
+            &Number::Integer(i) => i == 0,
+            &Number::BigInt(ref b) => b.is_zero(),
+            &Number::Ratio(ref r) => r.is_zero(),
+            &Number::Float(f) => f == 0.0
+        }
+    }
+}
+
+pub fn big_to_value(big: BigInt) -> Value {
+    match big.to_i64() {
+        Some(i) => Value::Integer(i),
+        None => Value::BigInt(big)
+    }
+}
+
+pub fn ratio_to_value(ratio: BigRational) -> Value {
+    if ratio.is_integer() {
+        big_to_value(ratio.to_integer())
+    }
+    else {
+        Value::Ratio(ratio)
+    }
+}
+
+//the pair of numbers converted to their common type
+enum Pair {
+    Integer(INT, INT),
+    BigInt(BigInt, BigInt),
+    Ratio(BigRational, BigRational),
+    Float(FLOAT, FLOAT)
+}
+
+fn pair(n_1: &Number, n_2: &Number) -> Pair {
+    match (n_1, n_2) {
+        (&Number::Integer(i_1), &Number::Integer(i_2)) => Pair::Integer(i_1, i_2),
+        (&Number::Float(_), _) | (_, &Number::Float(_)) => Pair::Float(n_1.to_float(), n_2.to_float()),
+        (&Number::Ratio(_), _) | (_, &Number::Ratio(_)) => Pair::Ratio(n_1.to_ratio().unwrap(), n_2.to_ratio().unwrap()),
+        _ => Pair::BigInt(n_1.to_big(), n_2.to_big())
+    }
+}
+
+pub fn add(n_1: Number, n_2: Number) -> Number {
+    match pair(&n_1, &n_2) {
+        Pair::Integer(i_1, i_2) => match i_1.checked_add(i_2) {
+            Some(i) => Number::Integer(i),
+            None => Number::BigInt(BigInt::from(i_1) + i_2)
+        },
+        Pair::BigInt(b_1, b_2) => Number::BigInt(b_1 + b_2),
+        Pair::Ratio(r_1, r_2) => Number::Ratio(r_1 + r_2),
+        Pair::Float(f_1, f_2) => Number::Float(f_1 + f_2)
+    }
+}
+
+pub fn sub(n_1: Number, n_2: Number) -> Number {
+    match pair(&n_1, &n_2) {
+        Pair::Integer(i_1, i_2) => match i_1.checked_sub(i_2) {
+            Some(i) => Number::Integer(i),
+            None => Number::BigInt(BigInt::from(i_1) - i_2)
+        },
+        Pair::BigInt(b_1, b_2) => Number::BigInt(b_1 - b_2),
+        Pair::Ratio(r_1, r_2) => Number::Ratio(r_1 - r_2),
+        Pair::Float(f_1, f_2) => Number::Float(f_1 - f_2)
+    }
+}
+
+pub fn mul(n_1: Number, n_2: Number) -> Number {
+    match pair(&n_1, &n_2) {
+        Pair::Integer(i_1, i_2) => match i_1.checked_mul(i_2) {
+            Some(i) => Number::Integer(i),
+            None => Number::BigInt(BigInt::from(i_1) * i_2)
+        },
+        Pair::BigInt(b_1, b_2) => Number::BigInt(b_1 * b_2),
+        Pair::Ratio(r_1, r_2) => Number::Ratio(r_1 * r_2),
+        Pair::Float(f_1, f_2) => Number::Float(f_1 * f_2)
+    }
+}
+
+//the remainder has the sign of the dividend, None for an integer division by zero
+pub fn rem(n_1: Number, n_2: Number) -> Option<Number> {
+    match pair(&n_1, &n_2) {
+        Pair::Float(f_1, f_2) => Some(Number::Float(f_1 % f_2)),
+        _ if n_2.is_zero() => None,
+        Pair::Integer(i_1, i_2) => match i_1.checked_rem(i_2) {
+            Some(i) => Some(Number::Integer(i)),
+            None => Some(Number::BigInt(BigInt::from(i_1) % i_2))
+        },
+        Pair::BigInt(b_1, b_2) => Some(Number::BigInt(b_1 % b_2)),
+        Pair::Ratio(r_1, r_2) => Some(Number::Ratio(r_1 % r_2))
+    }
+}
+
+//integers that do not divide evenly result in a ratio, None for an exact division by zero
+pub fn div(n_1: Number, n_2: Number) -> Option<Number> {
+    match pair(&n_1, &n_2) {
+        Pair::Float(f_1, f_2) => Some(Number::Float(f_1 / f_2)),
+        _ if n_2.is_zero() => None,
+        Pair::Integer(i_1, i_2) => match (i_1.checked_rem(i_2), i_1.checked_div(i_2)) {
+            (Some(0), Some(i)) => Some(Number::Integer(i)),
+            _ => Some(Number::Ratio(BigRational::new(BigInt::from(i_1), BigInt::from(i_2))))
+        },
+        Pair::BigInt(b_1, b_2) => match b_1.div_rem(&b_2) {
+            (quotient, ref remainder) if remainder.is_zero() => Some(Number::BigInt(quotient)),
+            _ => Some(Number::Ratio(BigRational::new(b_1, b_2)))
+        },
+        Pair::Ratio(r_1, r_2) => Some(Number::Ratio(r_1 / r_2))
+    }
+}
+
+//None if one of the numbers is nan
+pub fn compare(n_1: &Number, n_2: &Number) -> Option<Ordering> {
+    match pair(n_1, n_2) {
+        Pair::Integer(i_1, i_2) => Some(i_1.cmp(&i_2)),
+        Pair::BigInt(b_1, b_2) => Some(b_1.cmp(&b_2)),
+        Pair::Ratio(r_1, r_2) => Some(r_1.cmp(&r_2)),
+        Pair::Float(f_1, f_2) => f_1.partial_cmp(&f_2)
+    }
+}
+
+//the simplest ratio [smallest denominator] in the interval between lower and upper
+pub fn simplest_between(lower: &BigRational, upper: &BigRational) -> BigRational {
+    let zero = BigRational::zero();
+    if *lower <= zero && zero <= *upper {
+        return zero;
+    }
+    if *upper < zero {
+        return -simplest_between(&-upper, &-lower);
+    }
+    let floor = lower.floor();
+    if floor == *lower {
+        return floor;
+    }
+    if floor < upper.floor() {
+        return floor + BigRational::from_integer(BigInt::from(1));
+    }
+    let inner = simplest_between(&(upper - &floor).recip(), &(lower - &floor).recip());
+    floor + inner.recip()
+}
+
+//the simplest ratio that is rounded to the float, like 1/10 for 0.1, None for infinite floats and nan
+//the ratios between the midpoints to the neighbouring floats are rounded to it
+pub fn simplest_for_float(float: FLOAT) -> Option<BigRational> {
+    let exact = BigRational::from_float(float)?;
+    if float.fract() == 0.0 {
+        return Some(exact);
+    }
+    if float < 0.0 {
+        return simplest_for_float(-float).map(|x| -x);
+    }
+    let below = BigRational::from_float(FLOAT::from_bits(float.to_bits() - 1))?;
+    let above = BigRational::from_float(FLOAT::from_bits(float.to_bits() + 1))?;
+    let two = BigRational::from_integer(BigInt::from(2));
+    let simplest = simplest_between(&((&below + &exact) / &two), &((&exact + &above) / &two));
+    //a midpoint can be rounded to the neighbour, the exact value is used then
+    match simplest.to_f64() == Some(float) {
+        true => Some(simplest),
+        false => Some(exact)
+    }
+}
+
+pub fn abs(n: Number) -> Number {
+    match n {
+        Number::Integer(i) => match i.checked_abs() {
+            Some(i) => Number::Integer(i),
+            None => Number::BigInt(BigInt::from(i).abs())
+        },
+        Number::BigInt(b) => Number::BigInt(b.abs()),
+        Number::Ratio(r) => Number::Ratio(r.abs()),
+        Number::Float(f) => Number::Float(f.abs())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Rounding {
+    Floor,
+    Ceil,
+    Round,
+    Trunc
+}
+
+//integers stay unchanged, ratios become integers and floats stay floats
+pub fn round(n: Number, mode: Rounding) -> Number {
+    match n {
+        Number::Ratio(r) => Number::Ratio(match mode {
+            Rounding::Floor => r.floor(),
+            Rounding::Ceil => r.ceil(),
+            Rounding::Round => r.round(),
+            Rounding::Trunc => r.trunc()
+        }),
+        Number::Float(f) => Number::Float(match mode {
+            Rounding::Floor => f.floor(),
+            Rounding::Ceil => f.ceil(),
+            Rounding::Round => f.round(),
+            Rounding::Trunc => f.trunc()
+        }),
+        integer => integer
+    }
+}
+
+//exact if the base is exact and the exponent is an integer, None for zero to a negative power
+pub fn pow(base: Number, exponent: Number) -> Option<Number> {
+    let exact_exponent = exponent.to_integer().and_then(|x| x.to_i32());
+    match (base.to_ratio(), exact_exponent) {
+        (Some(ref ratio), Some(exp)) if base.is_exact() => {
+            if ratio.is_zero() && exp < 0 {
+                return None;
+            }
+            Some(Number::Ratio(ratio.pow(exp)))
+        },
+        _ => Some(Number::Float(base.to_float().powf(exponent.to_float())))
+    }
+}
+
+//about the number of bits of an exact power, the floor of its binary logarithm, zero if the power is a float
+//it is exact for powers of two, so pow and shl allow results of the same size
+pub fn power_bits(base: &Number, exponent: &Number) -> u64 {
+    let exp = match exponent.to_integer().and_then(|x| x.to_i32()) {
+        Some(exp) if base.is_exact() => exp.unsigned_abs() as u64,
+        _ => return 0
+    };
+    let log = |x: &BigInt| x.bits().saturating_sub(1);
+    match base.to_ratio() {
+        Some(ratio) => log(ratio.numer()).max(log(ratio.denom())).saturating_mul(exp),
+        None => 0
+    }
+}
+
+//the exact root if the number is exact and its root is exact, a float otherwise
+pub fn root(n: Number, degree: u32) -> Number {
+    let exact = match n {
+        Number::Float(_) => None,
+        _ if degree.is_multiple_of(2) && n.to_ratio().map(|x| x.is_negative()).unwrap_or(true) => None,
+        _ => {
+            let ratio = n.to_ratio().unwrap();
+            let numer = ratio.numer().nth_root(degree);
+            let denom = ratio.denom().nth_root(degree);
+            if numer.pow(degree) == *ratio.numer() && denom.pow(degree) == *ratio.denom() {
+                Some(Number::Ratio(BigRational::new(numer, denom)))
+            }
+            else {
+                None
+            }
+        }
+    };
+    match exact {
+        Some(exact) => exact,
+        None if degree == 2 => Number::Float(n.to_float().sqrt()),
+        None if degree == 3 => Number::Float(n.to_float().cbrt()),
+        None => Number::Float(n.to_float().powf(1.0 / degree as FLOAT))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,477 @@
+use list::List;
+use list::is_quote;
+use functions::lookup;
+use lambda::Lambda;
+use pattern::Pattern;
+use resolver::unquote;
+use resolver::requote;
+use scope::Scope;
+use stack::Stack;
+use symbol::Symbol;
+use value::Value;
+
+//the builtins of corelib::math and corelib::comp whose result only depends on their arguments,
+//a call with literal arguments is replaced by its result
+#[allow(clippy::redundant_static_lifetimes)]
+const PURE: [&'static str; 66] = [
+    "add", "+", "sub", "-", "mul", "*", "div", "/", "mod", "pow", "sqrt", "cbrt", "abs",
+    "sin", "cos", "tan", "asin", "acos", "atan", "atan2", "sinh", "cosh", "tanh", "asinh", "acosh", "atanh",
+    "exp", "log", "log2", "log10", "floor", "ceil", "round", "trunc", "min", "max", "quot", "rem", "gcd", "lcm",
+    "bit-and", "bit-or", "bit-xor", "bit-not", "shl", "shr", "numerator", "denominator", "exact->inexact", "rationalize",
+    "eq", "=", "ne", "!=", "lt", "<", "gt", ">", "le", "<=", "ge", ">=", "and", "or", "not", "compare"
+];
+
+//the builtins that read their arguments without evaluating them, the code in them is not changed
+#[allow(clippy::redundant_static_lifetimes)]
+const RAW: [&'static str; 7] = ["quote", "lambda", "deftest", "assert", "assert-eq", "assert-throws", "break"];
+
+//the number of cells up to which the body of a lambda is inlined
+const INLINE_LIMIT: usize = 24;
+
+//the largest number of bits a folded pow, shl, mul or lcm can have, larger results are only computed if the code runs
+const FOLD_BITS: u64 = 4096;
+
+//the names bound around the optimized code, with the lambda if calls of it can be inlined, the innermost name is the last one
+type Inlined = Vec<(Symbol, Option<Lambda>)>;
+
+//optimizes a value that is evaluated as an argument, the result is evaluated to the same value:
+//calls of pure builtins with literal arguments are folded, small lambdas are inlined and dead cond clauses are removed
+pub fn optimize(value: &Value) -> Value {
+    optimize_value(value, &mut Vec::new())
+}
+
+//optimizes a list that is evaluated as it is, like the body of a lambda, a folded value is quoted
+pub fn optimize_list(list: &List) -> List {
+    match optimize_call(list, &mut Vec::new(), true) {
+        Value::List(list) => list,
+        literal => List::from_cells(vec!(Value::Symbol(Symbol::new("quote")), literal))
+    }
+}
+
+#[allow(clippy::needless_borrowed_reference)]
+fn optimize_value(value: &Value, inlined: &mut Inlined) -> Value {
+    match value {
+        &Value::List(ref list) => optimize_call(list, inlined, false),
+        other => other.clone()
+    }
+}
+
+//the result is a list or, if the list is evaluated as an argument, any value with the same result
+//a list that is evaluated as it is can only be replaced by a literal, because 'set' defines variables in the list around it
+#[allow(clippy::needless_borrowed_reference, clippy::single_match)]
+fn optimize_call(list: &List, inlined: &mut Inlined, in_place: bool) -> Value {
+    let cells = list.cells();
+    let head = match cells.first() {
+        Some(head) => head,
+        None => return Value::List(list.clone())
+    };
+    match head {
+        &Value::Symbol(ref name) if lookup(name).is_some() => match &name[..] {
+            name if RAW.contains(&name) => Value::List(list.clone()),
+            "cond" => optimize_cond(list, inlined, in_place),
+            "let" | "let*" | "letrec" => optimize_let(list, inlined),
+            "match" => optimize_match(list, inlined),
+            name => {
+                let args = cells[1..].iter().map(|x| optimize_value(x, inlined)).collect::<Vec<_>>();
+                if PURE.contains(&name) && args.iter().all(is_literal) && is_small(name, &args) {
+                    match fold(head, &args) {
+                        Some(value) => return value,
+                        None => {}
+                    }
+                }
+                with_head(head.clone(), args)
+            }
+        },
+        _ => {
+            let args = cells[1..].iter().map(|x| optimize_value(x, inlined)).collect::<Vec<_>>();
+            match inline_target(head, inlined).and_then(|x| inline(&x, &args)) {
+                Some(code) => return optimize_call(&code, inlined, in_place),
+                None => {}
+            }
+            let head = optimize_value(head, inlined);
+            with_head(head, args)
+        }
+    }
+}
+
+fn with_head(head: Value, args: Vec<Value>) -> Value {
+    let mut cells = vec!(head);
+    cells.extend(args);
+    Value::List(List::from_cells(cells))
+}
+
+//evaluates the builtin, the result replaces the call if it can be written as a literal
+#[allow(clippy::needless_borrowed_reference)]
+fn fold(head: &Value, args: &[Value]) -> Option<Value> {
+    let builtin = match head {
+        &Value::Symbol(ref name) => lookup(name)?,
+        _ => return None
+    };
+    let mut cells = vec!(head.clone());
+    cells.extend_from_slice(args);
+    let mut stack = Stack::from_scopes(vec!(Scope::new()));
+    match builtin(&List::from_cells(cells), &mut stack) {
+        Ok(value) => literal(value),
+        Err(_) => None
+    }
+}
+
+//false if the exact result can have more than FOLD_BITS bits, the other builtins can not grow numbers that much
+fn is_small(name: &str, args: &[Value]) -> bool {
+    let bits = match args.iter().map(bits).collect::<Option<Vec<u64>>>() {
+        Some(bits) => bits,
+        None => return !matches!(name, "pow" | "shl" | "mul" | "*" | "lcm")
+    };
+    match (name, args.get(1)) {
+        ("pow", Some(&Value::Integer(exponent))) => bits[0].saturating_mul(exponent.unsigned_abs()) <= FOLD_BITS,
+        ("pow", Some(&Value::BigInt(_))) => false,
+        ("shl", Some(&Value::Integer(shift))) => bits[0].saturating_add(shift.unsigned_abs()) <= FOLD_BITS,
+        ("shl", _) => false,
+        ("mul", _) | ("*", _) | ("lcm", _) => bits.iter().sum::<u64>() <= FOLD_BITS,
+        _ => true
+    }
+}
+
+//the number of bits of an exact number, zero for floats and none for other values
+#[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+fn bits(value: &Value) -> Option<u64> {
+    match value {
+        &Value::Integer(int) => Some(64 - int.unsigned_abs().leading_zeros() as u64),
+        &Value::BigInt(ref int) => Some(int.bits()),
+        &Value::Ratio(ref ratio) => Some(ratio.numer().bits() + ratio.denom().bits()),
+        &Value::Float(_) => Some(0),
+        _ => None
+    }
+}
+
+//numbers, booleans, characters, nil, keywords and quoted values evaluate to themselves
+#[allow(clippy::needless_borrowed_reference)]
+fn is_literal(value: &Value) -> bool {
+    match value {
+        &Value::Integer(_) | &Value::Float(_) | &Value::BigInt(_) | &Value::Ratio(_) | &Value::Boolean(_) | &Value::Char(_) | &Value::Nil => true,
+        &Value::Symbol(ref symbol) => is_keyword(symbol),
+        &Value::List(ref list) => list.cells().len() == 2 && is_quote(list.cells().first().unwrap()),
+        _ => false
+    }
+}
+
+//the value as code that evaluates to it
+fn literal(value: Value) -> Option<Value> {
+    match value {
+        Value::Symbol(ref symbol) if is_keyword(symbol) => Some(value.clone()),
+        Value::Symbol(_) | Value::List(_) => Some(requote(value, true)),
+        value if is_literal(&value) => Some(value),
+        _ => None
+    }
+}
+
+fn is_keyword(symbol: &Symbol) -> bool {
+    symbol.starts_with(':') && symbol.len() > 1
+}
+
+//the quoted clauses are code, a clause with the condition false is removed and the ones after a true condition are never reached
+//if the first clause that remains is always taken, its result replaces the cond
+#[allow(clippy::len_zero, clippy::needless_borrowed_reference)]
+fn optimize_cond(list: &List, inlined: &mut Inlined, in_place: bool) -> Value {
+    let mut clauses = Vec::new();
+    let mut taken = None;
+    for cell in &list.cells()[1..] {
+        let clause = match unquote(cell) {
+            (&Value::List(ref clause), true) if clause.cells().len() == 2 => clause,
+            _ => {
+                clauses.push(optimize_value(cell, inlined));
+                continue;
+            }
+        };
+        let condition = optimize_value(&clause.cells()[0], inlined);
+        let result = optimize_value(&clause.cells()[1], inlined);
+        match condition {
+            Value::Boolean(false) => continue,
+            Value::Boolean(true) if clauses.len() == 0 => {
+                taken = Some(result);
+                break;
+            },
+            Value::Boolean(true) => {
+                clauses.push(requote(Value::List(List::from_cells(vec!(condition, result))), true));
+                break;
+            },
+            condition => clauses.push(requote(Value::List(List::from_cells(vec!(condition, result))), true))
+        }
+    }
+    match taken {
+        Some(result) => {
+            if is_literal(&result) || (!in_place && !is_set(&result)) {
+                return result;
+            }
+            clauses.push(requote(Value::List(List::from_cells(vec!(Value::Boolean(true), result))), true));
+        },
+        None if clauses.len() == 0 => return Value::List(list.clone()), //no condition is true, the error stays
+        None => {}
+    }
+    with_head(list.cells()[0].clone(), clauses)
+}
+
+//a call of set defines its variable in the list around it, so it can not take the place of that list
+#[allow(clippy::needless_borrowed_reference)]
+fn is_set(value: &Value) -> bool {
+    match value {
+        &Value::List(ref list) => match list.cells().first() {
+            Some(&Value::Symbol(ref name)) => name == "set" || name == "$",
+            _ => false
+        },
+        _ => false
+    }
+}
+
+//the values of the bindings and the body are optimized, calls of a small lambda that is bound to a name are inlined in the body
+//if nothing in the body could change the binding
+#[allow(clippy::needless_borrowed_reference)]
+fn optimize_let(list: &List, inlined: &mut Inlined) -> Value {
+    let (cells, quoted) = match list.cells().get(1).map(unquote) {
+        Some((&Value::List(ref bindings), quoted)) => (bindings.cells(), quoted),
+        _ => return Value::List(list.clone())
+    };
+    let mut patterns = Vec::new();
+    for cell in cells {
+        match cell {
+            &Value::List(ref binding) if binding.cells().len() == 2 => match Pattern::from_value(&binding.cells()[0]) {
+                Ok(pattern) => patterns.push(pattern),
+                Err(_) => return Value::List(list.clone())
+            },
+            _ => return Value::List(list.clone())
+        }
+    }
+    let outer = inlined.len();
+    let sequential = list.cells()[0] != Value::Symbol(Symbol::new("let"));
+    if sequential {
+        shadow(patterns.iter().flat_map(|x| x.names()), inlined);
+    }
+    let mut bindings = Vec::new();
+    for cell in cells {
+        let binding = match cell {
+            &Value::List(ref binding) => binding,
+            _ => unreachable!()
+        };
+        let value = optimize_value(&binding.cells()[1], inlined);
+        bindings.push(Value::List(List::from_cells(vec!(binding.cells()[0].clone(), value))));
+    }
+    inlined.truncate(outer);
+    let body = &list.cells()[2..];
+    let all_names = patterns.iter().flat_map(|x| x.names()).collect::<Vec<_>>();
+    shadow(all_names.iter().cloned(), inlined);
+    let mut lambdas = Vec::new();
+    for (pattern, binding) in patterns.iter().zip(&bindings) {
+        match (pattern, inline_target(&binding_value(binding), inlined)) {
+            //a name that is bound twice keeps the last value
+            (&Pattern::Bind(ref name), Some(lambda)) if all_names.iter().filter(|x| *x == name).count() == 1 => {
+                lambdas.push((name.clone(), Some(lambda)));
+            },
+            _ => {}
+        }
+    }
+    let names = lambdas.iter().map(|x| x.0.clone()).collect::<Vec<_>>();
+    if body.iter().all(|x| cannot_rebind(x, &names)) {
+        inlined.extend(lambdas);
+    }
+    let mut result = vec!(list.cells()[0].clone(), requote(Value::List(List::from_cells(bindings)), quoted));
+    result.extend(body.iter().map(|x| optimize_value(x, inlined)));
+    inlined.truncate(outer);
+    Value::List(List::from_cells(result))
+}
+
+#[allow(clippy::needless_borrowed_reference)]
+fn binding_value(binding: &Value) -> Value {
+    match binding {
+        &Value::List(ref binding) => binding.cells()[1].clone(),
+        _ => Value::Nil
+    }
+}
+
+//the scrutinee and the clauses are optimized, the names of a pattern hide the outer ones in its clause
+#[allow(clippy::len_zero, clippy::needless_borrowed_reference)]
+fn optimize_match(list: &List, inlined: &mut Inlined) -> Value {
+    let cells = list.cells();
+    if cells.len() < 2 {
+        return Value::List(list.clone());
+    }
+    let mut result = vec!(cells[0].clone(), optimize_value(&cells[1], inlined));
+    for cell in &cells[2..] {
+        let (clause, quoted) = match unquote(cell) {
+            (&Value::List(ref clause), quoted) if clause.cells().len() > 0 => (clause, quoted),
+            _ => {
+                result.push(cell.clone());
+                continue;
+            }
+        };
+        let pattern = match Pattern::from_value(&clause.cells()[0]) {
+            Ok(pattern) => pattern,
+            Err(_) => {
+                result.push(cell.clone());
+                continue;
+            }
+        };
+        let outer = inlined.len();
+        shadow(pattern.names().into_iter(), inlined);
+        let mut clause_cells = vec!(clause.cells()[0].clone());
+        clause_cells.extend(clause.cells()[1..].iter().map(|x| optimize_value(x, inlined)));
+        inlined.truncate(outer);
+        result.push(requote(Value::List(List::from_cells(clause_cells)), quoted));
+    }
+    Value::List(List::from_cells(result))
+}
+
+fn shadow<I>(names: I, inlined: &mut Inlined) where I: Iterator<Item=Symbol> {
+    for name in names {
+        inlined.push((name, None));
+    }
+}
+
+//the lambda that is called by the head of a list, a lambda literal or a name bound to an inlinable lambda
+#[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+fn inline_target(head: &Value, inlined: &Inlined) -> Option<Lambda> {
+    let name = match head {
+        &Value::List(ref list) => return match list.cells() {
+            [Value::Lambda(ref lambda)] => Some(lambda.clone()),
+            _ => None
+        },
+        &Value::Symbol(ref name) => name,
+        &Value::Variable(ref variable) => variable.symbol(),
+        _ => return None
+    };
+    inlined.iter().rev().find(|x| x.0 == *name).and_then(|x| x.1.clone())
+}
+
+//the body of the lambda with the arguments in place of the parameters, if the body is small and only uses pure builtins,
+//an argument that is not a literal has to be used and free of side effects, so that it is evaluated at least once without changing anything
+fn inline(lambda: &Lambda, args: &[Value]) -> Option<List> {
+    let (names, body) = lambda.simple_parts()?;
+    let body = Value::List(body.clone());
+    if names.len() != args.len() || size(&body) > INLINE_LIMIT || !is_pure(&body, Some(&names)) {
+        return None;
+    }
+    for (name, arg) in names.iter().zip(args) {
+        let inlinable = is_literal(arg) || (is_pure(arg, None) && uses(&body, name));
+        if !inlinable {
+            return None;
+        }
+    }
+    match substitute(&body, &names, args) {
+        Value::List(list) => Some(list),
+        _ => None
+    }
+}
+
+//true if the code only calls pure builtins and, if the names are given, only reads these variables
+#[allow(clippy::needless_borrowed_reference)]
+fn is_pure(value: &Value, names: Option<&[Symbol]>) -> bool {
+    match value {
+        &Value::Symbol(ref symbol) => is_keyword(symbol) || can_read(symbol, names),
+        &Value::Variable(ref variable) => can_read(variable.symbol(), names),
+        &Value::List(ref list) => match list.cells().split_first() {
+            Some((head, _)) if is_quote(head) => true,
+            Some((&Value::Symbol(ref name), args)) if PURE.contains(&&name[..]) => args.iter().all(|x| is_pure(x, names)),
+            Some(_) => false,
+            None => true
+        },
+        &Value::Lambda(_) | &Value::Map(_) | &Value::Atom(_) | &Value::Record(_) | &Value::LazySeq(_) => false,
+        _ => true
+    }
+}
+
+fn can_read(symbol: &Symbol, names: Option<&[Symbol]>) -> bool {
+    match names {
+        Some(names) => names.contains(symbol),
+        None => true
+    }
+}
+
+//true if the code can not change the binding of the names: it has no lambdas and only calls pure builtins,
+//the lambdas bound to the names and the builtins that evaluate code like cond, seq, let and match
+#[allow(clippy::len_zero, clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+fn cannot_rebind(value: &Value, names: &[Symbol]) -> bool {
+    let list = match value {
+        &Value::List(ref list) => list,
+        &Value::Lambda(_) => return false,
+        _ => return true
+    };
+    let (head, args) = match list.cells().split_first() {
+        Some(parts) => parts,
+        None => return true
+    };
+    let name = match head {
+        &Value::Symbol(ref name) => name,
+        &Value::Variable(ref variable) => variable.symbol(),
+        _ => return false
+    };
+    let all = |cells: &[Value]| cells.iter().all(|x| cannot_rebind(x, names));
+    match &name[..] {
+        "quote" => args.iter().all(|x| !contains_lambda(x)),
+        "cond" => args.iter().all(|x| match unquote(x) {
+            (&Value::List(ref clause), true) => all(clause.cells()),
+            (other, _) => cannot_rebind(other, names)
+        }),
+        "let" | "let*" | "letrec" if args.len() > 0 => {
+            let bindings = match unquote(&args[0]) {
+                (&Value::List(ref bindings), _) => bindings.cells().iter().all(|x| match x {
+                    &Value::List(ref binding) => all(&binding.cells()[1..]),
+                    _ => false
+                }),
+                _ => false
+            };
+            bindings && all(&args[1..])
+        },
+        "match" if args.len() > 0 => cannot_rebind(&args[0], names) && args[1..].iter().all(|x| match unquote(x) {
+            (&Value::List(ref clause), _) if clause.cells().len() > 0 => all(&clause.cells()[1..]),
+            _ => false
+        }),
+        "seq" => all(args),
+        name if PURE.contains(&name) => all(args),
+        _ => names.contains(name) && all(args)
+    }
+}
+
+#[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+fn contains_lambda(value: &Value) -> bool {
+    match value {
+        &Value::List(ref list) => list.cells().iter().any(contains_lambda),
+        &Value::Lambda(_) => true,
+        _ => false
+    }
+}
+
+#[allow(clippy::needless_borrowed_reference)]
+fn size(value: &Value) -> usize {
+    match value {
+        &Value::List(ref list) => list.cells().iter().map(size).sum::<usize>() + 1,
+        _ => 1
+    }
+}
+
+#[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+fn uses(value: &Value, name: &Symbol) -> bool {
+    match value {
+        &Value::Symbol(ref symbol) => symbol == name,
+        &Value::Variable(ref variable) => variable.symbol() == name,
+        &Value::List(ref list) => !is_literal(value) && list.cells().iter().skip(1).any(|x| uses(x, name)),
+        _ => false
+    }
+}
+
+//replaces the variables with the arguments, the heads of the lists are names of builtins and quoted values stay unchanged
+#[allow(clippy::len_zero, clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+fn substitute(value: &Value, names: &[Symbol], args: &[Value]) -> Value {
+    let position = match value {
+        &Value::Symbol(ref symbol) => names.iter().position(|x| x == symbol),
+        &Value::Variable(ref variable) => names.iter().position(|x| x == variable.symbol()),
+        &Value::List(ref list) if !is_literal(value) && list.cells().len() > 0 => {
+            let mut cells = vec!(list.cells()[0].clone());
+            cells.extend(list.cells()[1..].iter().map(|x| substitute(x, names, args)));
+            return Value::List(List::from_cells(cells));
+        },
+        _ => None
+    };
+    match position {
+        Some(index) => args[index].clone(),
+        None => value.clone()
+    }
+}
\ No newline at end of file
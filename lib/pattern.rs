@@ -0,0 +1,222 @@
+use std::fmt::{
+    Display,
+    Formatter
+};
+use std::fmt::Result as FmtResult;
+use std::cmp::Ordering;
+
+use error::Error;
+use list::List;
+use number;
+use number::Number;
+use symbol::Symbol;
+use value::Value;
+
+//the types that can be used in type patterns like (int n)
+const TYPE_NAMES: [&str; 13] = ["nil", "list", "float", "int", "ratio", "symbol", "lambda", "boolean", "char", "map", "number", "string", "any"];
+
+//the shape of a value in a binding position, like the names in '((a b & rest) xs) of a let
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum Pattern {
+    //binds the whole value to the name
+    Bind(Symbol),
+    //_ matches everything without binding it
+    Ignore,
+    //numbers, characters, booleans, nil, keywords and quoted symbols match equal values
+    Literal(Value),
+    //(int n) matches values of the type and binds them to the inner pattern
+    Type(String, Box<Pattern>),
+    //the patterns of the elements and an optional pattern after & for the remaining ones
+    List(Vec<Pattern>, Option<Box<Pattern>>),
+    //(:keys a b) binds the values of the keys :a and :b of a map
+    Keys(Vec<Symbol>),
+    //(:map key pattern...) matches maps that contain the keys with values matching the patterns
+    Map(Vec<(Value, Pattern)>)
+}
+
+impl Pattern {
+    #[allow(clippy::needless_borrowed_reference)]
+    pub fn from_value(value: &Value) -> Result<Pattern, Error> {
+        match value {
+            &Value::Symbol(ref symbol) if symbol == "_" => Ok(Pattern::Ignore),
+            &Value::Symbol(ref symbol) if symbol.starts_with(':') && symbol.len() > 1 => Ok(Pattern::Literal(value.clone())),
+            &Value::Symbol(ref symbol) if !symbol.starts_with(':') && !symbol.starts_with('&') => Ok(Pattern::Bind(symbol.clone())),
+            &Value::Symbol(_) => Err(Error::new(format!("invalid pattern: {}.", value))),
+            &Value::List(ref list) => {
+                match (list.cells().first(), list.cells().len()) {
+                    (Some(&Value::Symbol(ref symbol)), 2) if symbol == "quote" => {
+                        return Ok(Pattern::Literal(list.cells().get(1).unwrap().clone()));
+                    },
+                    (Some(&Value::Symbol(ref symbol)), 2) if TYPE_NAMES.contains(&&symbol[..]) => {
+                        return Ok(Pattern::Type(symbol.to_string(), Box::new(Pattern::from_value(list.cells().get(1).unwrap())?)));
+                    },
+                    (Some(&Value::Symbol(ref symbol)), _) if symbol == ":map" => {
+                        let cells = &list.cells()[1..];
+                        if !cells.len().is_multiple_of(2) {
+                            return Err(Error::new(format!("invalid pattern: expected a pattern for every key in {}.", value)));
+                        }
+                        let mut entries = Vec::new();
+                        for entry in cells.chunks(2) {
+                            entries.push((entry[0].clone(), Pattern::from_value(&entry[1])?));
+                        }
+                        return Ok(Pattern::Map(entries));
+                    },
+                    _ => {}
+                }
+                match list.cells().first() {
+                    Some(&Value::Symbol(ref symbol)) if symbol == ":keys" => {
+                        let mut names = Vec::new();
+                        for cell in list.cells().iter().skip(1) {
+                            match cell {
+                                &Value::Symbol(ref name) => names.push(name.clone()),
+                                other => return Err(Error::new(format!("invalid pattern: expected names after :keys, found {}.", other)))
+                            }
+                        }
+                        return Ok(Pattern::Keys(names));
+                    },
+                    _ => {}
+                }
+                let mut elements = Vec::new();
+                let mut rest = None;
+                let mut cell_iter = list.cells().iter();
+                while let Some(cell) = cell_iter.next() {
+                    if *cell == Value::Symbol(Symbol::new("&")) {
+                        rest = match (cell_iter.next(), cell_iter.next()) {
+                            (Some(rest), None) => Some(Box::new(Pattern::from_value(rest)?)),
+                            _ => return Err(Error::new(format!("invalid pattern: expected one pattern after '&' in {}.", value)))
+                        };
+                    }
+                    else {
+                        elements.push(Pattern::from_value(cell)?);
+                    }
+                }
+                Ok(Pattern::List(elements, rest))
+            },
+            other => Ok(Pattern::Literal(other.clone()))
+        }
+    }
+
+    //the names that are bound by the pattern
+    #[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference, clippy::single_match)]
+    pub fn names(&self) -> Vec<Symbol> {
+        match self {
+            &Pattern::Bind(ref name) => vec!(name.clone()),
+            &Pattern::Ignore => Vec::new(),
+            &Pattern::Literal(_) => Vec::new(),
+            &Pattern::Type(_, ref inner) => inner.names(),
+            &Pattern::List(ref elements, ref rest) => {
+                let mut names = elements.iter().flat_map(|x| x.names()).collect::<Vec<_>>();
+                match rest {
+                    &Some(ref rest) => names.append(&mut rest.names()),
+                    &None => {}
+                }
+                names
+            },
+            &Pattern::Keys(ref names) => names.clone(),
+            &Pattern::Map(ref entries) => entries.iter().flat_map(|x| x.1.names()).collect()
+        }
+    }
+
+    //adds the bindings to the vector, returns false if the value does not have the shape of the pattern
+    #[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+    pub fn matches(&self, value: &Value, bindings: &mut Vec<(Symbol, Value)>) -> bool {
+        match (self, value) {
+            (&Pattern::Bind(ref name), value) => {
+                bindings.push((name.clone(), value.clone()));
+                true
+            },
+            (&Pattern::Ignore, _) => true,
+            (&Pattern::Literal(ref literal), value) => literal_matches(literal, value),
+            (&Pattern::Type(ref type_name, ref inner), value) => type_matches(type_name, value) && inner.matches(value, bindings),
+            (&Pattern::List(ref elements, ref rest), &Value::List(ref list)) => {
+                let cells = list.cells();
+                let length_matches = match rest {
+                    &Some(_) => cells.len() >= elements.len(),
+                    &None => cells.len() == elements.len()
+                };
+                if !length_matches || !elements.iter().zip(cells.iter()).all(|(pattern, cell)| pattern.matches(cell, bindings)) {
+                    return false;
+                }
+                match rest {
+                    &Some(ref rest) => rest.matches(&Value::List(List::from_cells(cells[elements.len()..].to_vec())), bindings),
+                    &None => true
+                }
+            },
+            (&Pattern::List(_, _), &Value::Nil) => self.matches(&Value::List(List::empty()), bindings),
+            (&Pattern::Keys(ref names), &Value::Map(ref map)) => {
+                for name in names {
+                    match map.get(&Value::Symbol(Symbol::from(format!(":{}", name)))) {
+                        Some(value) => bindings.push((name.clone(), value.clone())),
+                        None => return false
+                    }
+                }
+                true
+            },
+            (&Pattern::Map(ref entries), &Value::Map(ref map)) => {
+                entries.iter().all(|&(ref key, ref pattern)| match map.get(key) {
+                    Some(value) => pattern.matches(value, bindings),
+                    None => false
+                })
+            },
+            _ => false
+        }
+    }
+
+    //the bindings of the value, fails if the value does not have the shape of the pattern
+    pub fn destructure(&self, value: &Value) -> Result<Vec<(Symbol, Value)>, Error> {
+        let mut bindings = Vec::new();
+        match self.matches(value, &mut bindings) {
+            true => Ok(bindings),
+            false => Err(Error::new(format!("the value {} does not match the pattern {}.", value, self)))
+        }
+    }
+}
+
+impl Display for Pattern {
+    #[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference, clippy::single_match)]
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            &Pattern::Bind(ref name) => write!(f, "{}", name),
+            &Pattern::Ignore => write!(f, "_"),
+            &Pattern::Literal(Value::Symbol(ref symbol)) if !symbol.starts_with(':') => write!(f, "'{}", symbol),
+            &Pattern::Literal(ref literal) => write!(f, "{}", literal),
+            &Pattern::Type(ref type_name, ref inner) => write!(f, "({} {})", type_name, inner),
+            &Pattern::List(ref elements, ref rest) => {
+                let mut parts = elements.iter().map(|x| format!("{}", x)).collect::<Vec<_>>();
+                match rest {
+                    &Some(ref rest) => parts.push(format!("& {}", rest)),
+                    &None => {}
+                }
+                write!(f, "({})", parts.join(" "))
+            },
+            &Pattern::Keys(ref names) => write!(f, "(:keys {})", names.iter().map(|x| x.as_str()).collect::<Vec<_>>().join(" ")),
+            &Pattern::Map(ref entries) => {
+                let parts = entries.iter().map(|x| format!("{} {}", x.0, x.1)).collect::<Vec<_>>();
+                write!(f, "(:map {})", parts.join(" "))
+            }
+        }
+    }
+}
+
+//numbers are compared by their value, nil matches the empty list
+fn literal_matches(literal: &Value, value: &Value) -> bool {
+    match (Number::from_value(literal), Number::from_value(value)) {
+        (Some(n_1), Some(n_2)) => return number::compare(&n_1, &n_2) == Some(Ordering::Equal),
+        (Some(_), None) | (None, Some(_)) => return false,
+        _ => {}
+    }
+    match (literal, value) {
+        (&Value::Nil, &Value::List(ref list)) | (&Value::List(ref list), &Value::Nil) => list.cells().is_empty(),
+        (literal, value) => literal == value
+    }
+}
+
+fn type_matches(type_name: &str, value: &Value) -> bool {
+    match type_name {
+        "any" => true,
+        "number" => Number::from_value(value).is_some(),
+        "string" => value.is_list_and_string(),
+        "list" => matches!(value, &Value::List(_) | &Value::Nil),
+        type_name => value.type_str() == type_name
+    }
+}
\ No newline at end of file
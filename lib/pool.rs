@@ -0,0 +1,162 @@
+use error::Error;
+use gc::ThreadGuard;
+
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::OnceLock;
+use std::thread;
+
+//the threads that run pmap, future and spawn, they are started when the pool is used first
+//a worker that waits for a result runs the queued jobs in the meantime, so jobs that wait for other jobs can not block the pool.
+//other threads only wait, so they are never stuck in a job that waits for something they would do afterwards
+
+//the stack of a worker, twice the 8 MiB the main thread usually gets, because a waiting worker runs other jobs on it, see help
+const STACK_SIZE: usize = 16 * 1024 * 1024;
+//the pool can have at most this many threads for every core, each of them reserves the stack above
+const MAX_THREADS_PER_CORE: usize = 16;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+enum Task {
+    Run(Job),
+    //ends one worker, used when the pool gets smaller
+    Stop
+}
+
+struct Pool {
+    queue: Mutex<VecDeque<Task>>,
+    ready: Condvar,
+    //the number of workers, zero until the pool is used or its size is set
+    size: Mutex<usize>
+}
+
+thread_local! {
+    static WORKER: Cell<bool> = const { Cell::new(false) };
+}
+
+fn pool() -> &'static Pool {
+    static POOL: OnceLock<Pool> = OnceLock::new();
+    POOL.get_or_init(|| Pool {
+        queue: Mutex::new(VecDeque::new()),
+        ready: Condvar::new(),
+        size: Mutex::new(0)
+    })
+}
+
+//a panic in a job does not make the pool unusable
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|x| x.into_inner())
+}
+
+//the number of threads the pool starts with, one for every core
+pub fn default_size() -> usize {
+    thread::available_parallelism().map(|x| x.get()).unwrap_or(4)
+}
+
+//the largest size of the pool
+pub fn max_size() -> usize {
+    default_size() * MAX_THREADS_PER_CORE
+}
+
+//the number of workers, the default size if the pool was not used yet
+pub fn size() -> usize {
+    match *lock(&pool().size) {
+        0 => default_size(),
+        size => size
+    }
+}
+
+//starts or stops workers until the pool has the size, a worker that stops finishes the jobs that were queued before
+//if a thread can not be started, the workers that were started stay and the pool is smaller than the size
+#[allow(clippy::useless_format)]
+pub fn set_size(size: usize) -> Result<(), Error> {
+    if size > max_size() {
+        return Err(Error::new(format!("the pool can have at most {} threads, found {}.", max_size(), size)));
+    }
+    let pool = pool();
+    let mut current = lock(&pool.size);
+    let size = size.max(1);
+    while *current < size {
+        match thread::Builder::new().stack_size(STACK_SIZE).spawn(work) {
+            Ok(_) => *current += 1,
+            Err(err) => return Err(Error::new(format!("could not start a thread of the pool: {}.", err)))
+        }
+    }
+    if *current > size {
+        let mut queue = lock(&pool.queue);
+        for _ in size..*current {
+            queue.push_back(Task::Stop);
+        }
+        *current = size;
+        pool.ready.notify_all();
+    }
+    Ok(())
+}
+
+//queues the job, it runs on a worker or on a worker that waits for a result
+//the job counts as a running interpreter thread from now on until it drops the guard, see gc
+pub fn submit<F>(job: F) where F: FnOnce(ThreadGuard) + Send + 'static {
+    let pool = pool();
+    if *lock(&pool.size) == 0 {
+        let _ = set_size(default_size());
+    }
+    let guard = ThreadGuard::new();
+    //no thread of the pool could be started, so the job runs here
+    if *lock(&pool.size) == 0 {
+        return run(Box::new(move || job(guard)));
+    }
+    lock(&pool.queue).push_back(Task::Run(Box::new(move || job(guard))));
+    pool.ready.notify_one();
+}
+
+//runs one queued job if the current thread is a worker, false if it did not run one
+#[allow(clippy::match_ref_pats)]
+pub fn help() -> bool {
+    if !WORKER.with(|x| x.get()) {
+        return false;
+    }
+    let job = {
+        let mut queue = lock(&pool().queue);
+        match queue.iter().position(|x| match x { &Task::Run(_) => true, &Task::Stop => false }) {
+            Some(index) => queue.remove(index),
+            None => None
+        }
+    };
+    match job {
+        Some(Task::Run(job)) => {
+            run(job);
+            true
+        },
+        _ => false
+    }
+}
+
+fn work() {
+    WORKER.with(|x| x.set(true));
+    let pool = pool();
+    loop {
+        let task = {
+            let mut queue = lock(&pool.queue);
+            loop {
+                match queue.pop_front() {
+                    Some(task) => break task,
+                    None => queue = pool.ready.wait(queue).unwrap_or_else(|x| x.into_inner())
+                }
+            }
+        };
+        match task {
+            Task::Run(job) => run(job),
+            Task::Stop => return
+        }
+    }
+}
+
+//a panic in the job only ends the job
+fn run(job: Job) {
+    let _ = panic::catch_unwind(AssertUnwindSafe(job));
+}
\ No newline at end of file
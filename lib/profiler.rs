@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::time::{
+    Duration,
+    Instant
+};
+
+#[derive(Debug, Clone)]
+pub struct ProfileEntry {
+    pub calls: u64,
+    pub inclusive: Duration,
+    pub exclusive: Duration
+}
+
+#[derive(Debug)]
+struct Frame {
+    name: String,
+    start: Instant,
+    children: Duration
+}
+
+//counts calls and measures the time spent in builtins and named lambdas
+#[derive(Debug)]
+pub struct Profiler {
+    entries: HashMap<String, ProfileEntry>,
+    frames: Vec<Frame>,
+    stacks: HashMap<String, Duration>
+}
+
+impl Profiler {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Profiler {
+        Profiler {
+            entries: HashMap::new(),
+            frames: Vec::new(),
+            stacks: HashMap::new()
+        }
+    }
+
+    pub fn enter(&mut self, name: &str) {
+        self.frames.push(Frame {
+            name: name.to_owned(),
+            start: Instant::now(),
+            children: Duration::new(0, 0)
+        });
+    }
+
+    #[allow(clippy::single_match)]
+    pub fn leave(&mut self) {
+        let frame = match self.frames.pop() {
+            Some(frame) => frame,
+            None => return
+        };
+        let inclusive = frame.start.elapsed();
+        let exclusive = if inclusive > frame.children {
+            inclusive - frame.children
+        }
+        else {
+            Duration::new(0, 0)
+        };
+        //recursive calls are already contained in the inclusive time of the outermost call
+        let recursive = self.frames.iter().any(|x| x.name == frame.name);
+        match self.frames.last_mut() {
+            Some(parent) => parent.children += inclusive,
+            None => {}
+        }
+        let mut path = self.frames.iter().map(|x| &x.name[..]).collect::<Vec<&str>>();
+        path.push(&frame.name);
+        *self.stacks.entry(path.join(";")).or_insert(Duration::new(0, 0)) += exclusive;
+        let entry = self.entries.entry(frame.name).or_insert(ProfileEntry {
+            calls: 0,
+            inclusive: Duration::new(0, 0),
+            exclusive: Duration::new(0, 0)
+        });
+        entry.calls += 1;
+        entry.exclusive += exclusive;
+        if !recursive {
+            entry.inclusive += inclusive;
+        }
+    }
+
+    //all entries, the most expensive [by exclusive time] first
+    pub fn entries(&self) -> Vec<(&String, &ProfileEntry)> {
+        let mut entries = self.entries.iter().collect::<Vec<_>>();
+        entries.sort_by(|a, b| b.1.exclusive.cmp(&a.1.exclusive).then(a.0.cmp(b.0)));
+        entries
+    }
+
+    pub fn table(&self) -> String {
+        let entries = self.entries();
+        let mut width = "function".len();
+        for &(name, _) in &entries {
+            if name.len() > width {
+                width = name.len();
+            }
+        }
+        let mut buffer = format!("{:<width$}  {:>10}  {:>15}  {:>15}\n", "function", "calls", "inclusive [ms]", "exclusive [ms]", width = width);
+        for (name, entry) in entries {
+            buffer.push_str(&format!("{:<width$}  {:>10}  {:>15.3}  {:>15.3}\n", name, entry.calls, millis(entry.inclusive), millis(entry.exclusive), width = width));
+        }
+        buffer
+    }
+
+    //the collapsed stack format understood by flamegraph tools, one 'a;b;c <microseconds>' line per stack
+    pub fn collapsed(&self) -> String {
+        let mut stacks = self.stacks.iter().collect::<Vec<_>>();
+        stacks.sort();
+        let mut buffer = String::new();
+        for (path, time) in stacks {
+            buffer.push_str(&format!("{} {}\n", path, time.as_micros()));
+        }
+        buffer
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
\ No newline at end of file
@@ -0,0 +1,41 @@
+use symbol::Symbol;
+use value::Value;
+
+//a value of a type defined with defrecord, the fields keep the order of the definition
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct Record {
+    name: Symbol,
+    fields: Vec<(Symbol, Value)>
+}
+
+impl Record {
+    #[allow(clippy::redundant_field_names)]
+    pub fn new(name: Symbol, fields: Vec<(Symbol, Value)>) -> Record {
+        Record {
+            name: name,
+            fields: fields
+        }
+    }
+
+    pub fn name(&self) -> &Symbol {
+        &self.name
+    }
+
+    pub fn fields(&self) -> &Vec<(Symbol, Value)> {
+        &self.fields
+    }
+
+    pub fn get(&self, field: &str) -> Option<&Value> {
+        self.fields.iter().find(|x| x.0 == field).map(|x| &x.1)
+    }
+
+    //a copy of the record with a new value for the field, None if the record has no such field
+    pub fn with(&self, field: &str, value: Value) -> Option<Record> {
+        let mut retval = self.clone();
+        match retval.fields.iter_mut().find(|x| x.0 == field) {
+            Some(entry) => entry.1 = value,
+            None => return None
+        }
+        Some(retval)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,286 @@
+use list::List;
+use list::is_quote;
+use lambda::Params;
+use pattern::Pattern;
+use functions::lookup;
+use symbol::Symbol;
+use value::Value;
+
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::fmt::Result as FmtResult;
+use std::fmt::Formatter;
+use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering as AtomicOrdering;
+
+//a variable in the body of a lambda after the resolver pass
+//a local variable has the number of scopes above the one that binds it and its slot in that scope,
+//the address is only used if that slot still contains the variable, otherwise the variable is searched by name,
+//because variables that are not bound by the lambda are looked up in the scopes of the callers
+#[derive(Clone)]
+pub struct Variable {
+    symbol: Symbol,
+    address: Option<(usize, usize)>,
+    //the slot of the variable in the global scope the last time it was found there, shared by all clones
+    global_slot: Arc<AtomicUsize>
+}
+
+impl Variable {
+    #[allow(clippy::redundant_field_names)]
+    pub fn new(symbol: Symbol, address: Option<(usize, usize)>) -> Variable {
+        Variable {
+            symbol: symbol,
+            address: address,
+            global_slot: Arc::new(AtomicUsize::new(0))
+        }
+    }
+
+    pub fn symbol(&self) -> &Symbol {
+        &self.symbol
+    }
+
+    pub fn address(&self) -> Option<(usize, usize)> {
+        self.address
+    }
+
+    pub fn global_slot(&self) -> usize {
+        self.global_slot.load(AtomicOrdering::Relaxed)
+    }
+
+    pub fn set_global_slot(&self, slot: usize) {
+        self.global_slot.store(slot, AtomicOrdering::Relaxed);
+    }
+}
+
+impl PartialEq for Variable {
+    fn eq(&self, other: &Variable) -> bool {
+        self.symbol == other.symbol && self.address == other.address
+    }
+}
+
+impl PartialOrd for Variable {
+    fn partial_cmp(&self, other: &Variable) -> Option<Ordering> {
+        Some(self.symbol.cmp(&other.symbol).then(self.address.cmp(&other.address)))
+    }
+}
+
+impl Debug for Variable {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self.address {
+            Some((depth, slot)) => write!(f, "{}@{}:{}", self.symbol, depth, slot),
+            None => write!(f, "{}", self.symbol)
+        }
+    }
+}
+
+//rewrites the variables of a lambda body, the parameters are the innermost scope when the body is evaluated
+//code that is quoted or evaluated by builtins that read it without resolving it is not changed
+//lambdas and match clauses without names and lists get no scope, every other binding form does
+pub fn resolve_lambda(params: &Params, body: &List) -> List {
+    let names = unique(params.names());
+    let mut frames = match names.len() {
+        0 => Vec::new(),
+        _ => vec!(names)
+    };
+    resolve_list(body, &mut frames)
+}
+
+//the names of the scopes around the code that is resolved, the innermost scope is the last one
+type Frames = Vec<Vec<Symbol>>;
+
+#[allow(clippy::needless_borrowed_reference)]
+fn resolve_list(list: &List, frames: &mut Frames) -> List {
+    let cells = list.cells();
+    let head = match cells.first() {
+        Some(head) => head,
+        None => return list.clone()
+    };
+    match head {
+        &Value::Symbol(ref name) if lookup(name).is_some() => match &name[..] {
+            "let" => resolve_let(list, frames, false),
+            "let*" => resolve_let(list, frames, true),
+            "letrec" => resolve_letrec(list, frames),
+            "match" => resolve_match(list, frames),
+            "quote" | "lambda" | "deftest" | "assert" | "assert-eq" | "assert-throws" | "break" => list.clone(),
+            _ => with_head(head.clone(), &cells[1..], frames)
+        },
+        &Value::Symbol(_) | &Value::Variable(_) | &Value::List(_) => {
+            let head = resolve_value(head, frames);
+            with_head(head, &cells[1..], frames)
+        },
+        &Value::Lambda(_) => with_head(head.clone(), &cells[1..], frames),
+        _ => list.clone()
+    }
+}
+
+fn with_head(head: Value, args: &[Value], frames: &mut Frames) -> List {
+    let mut cells = Vec::with_capacity(args.len() + 1);
+    cells.push(head);
+    cells.extend(args.iter().map(|x| resolve_value(x, frames)));
+    List::from_cells(cells)
+}
+
+#[allow(clippy::needless_borrowed_reference)]
+fn resolve_value(value: &Value, frames: &mut Frames) -> Value {
+    match value {
+        &Value::Symbol(ref symbol) if symbol.starts_with(':') && symbol.len() > 1 => value.clone(),
+        &Value::Symbol(ref symbol) => Value::Variable(variable(symbol, frames)),
+        &Value::Variable(ref variable_1) => Value::Variable(variable(variable_1.symbol(), frames)),
+        &Value::List(ref list) => Value::List(resolve_list(list, frames)),
+        other => other.clone()
+    }
+}
+
+#[allow(clippy::single_match)]
+fn variable(symbol: &Symbol, frames: &Frames) -> Variable {
+    for (i, frame) in frames.iter().enumerate().rev() {
+        match frame.iter().position(|x| x == symbol) {
+            Some(slot) => return Variable::new(symbol.clone(), Some((frames.len() - 1 - i, slot))),
+            None => {}
+        }
+    }
+    Variable::new(symbol.clone(), None)
+}
+
+//let evaluates the values outside of its scope, let* inside of it after the bindings before them
+fn resolve_let(list: &List, frames: &mut Frames, sequential: bool) -> List {
+    let (bindings, quoted) = match parse_bindings(list) {
+        Some(bindings) => bindings,
+        None => return list.clone()
+    };
+    let mut frame = Vec::new();
+    let mut resolved = Vec::new();
+    if sequential {
+        frames.push(Vec::new());
+    }
+    for (pattern, binding) in bindings {
+        let expr = resolve_value(binding.cells().get(1).unwrap(), frames);
+        resolved.push(Value::List(List::from_cells(vec!(binding.cells().first().unwrap().clone(), expr))));
+        for name in pattern.names() {
+            if !frame.contains(&name) {
+                frame.push(name);
+            }
+        }
+        if sequential {
+            *frames.last_mut().unwrap() = frame.clone();
+        }
+    }
+    if !sequential {
+        frames.push(frame);
+    }
+    let result = with_bindings(list, resolved, quoted, frames);
+    frames.pop();
+    result
+}
+
+//letrec binds all names before the values are evaluated
+#[allow(clippy::needless_borrowed_reference)]
+fn resolve_letrec(list: &List, frames: &mut Frames) -> List {
+    let (bindings, quoted) = match parse_bindings(list) {
+        Some(bindings) => bindings,
+        None => return list.clone()
+    };
+    frames.push(unique(bindings.iter().flat_map(|x| x.0.names()).collect()));
+    let resolved = bindings.iter().map(|&(_, ref binding)| {
+        let expr = resolve_value(binding.cells().get(1).unwrap(), frames);
+        Value::List(List::from_cells(vec!(binding.cells().first().unwrap().clone(), expr)))
+    }).collect();
+    let result = with_bindings(list, resolved, quoted, frames);
+    frames.pop();
+    result
+}
+
+//the patterns and bindings of a let form and whether they are quoted, none if the form is invalid
+#[allow(clippy::needless_borrowed_reference)]
+fn parse_bindings(list: &List) -> Option<(Vec<(Pattern, List)>, bool)> {
+    let (cells, quoted) = match list.cells().get(1).map(unquote) {
+        Some((&Value::List(ref bindings), quoted)) => (bindings.cells(), quoted),
+        _ => return None
+    };
+    let mut bindings = Vec::new();
+    for cell in cells {
+        match cell {
+            &Value::List(ref binding) if binding.cells().len() == 2 => match Pattern::from_value(binding.cells().first().unwrap()) {
+                Ok(pattern) => bindings.push((pattern, binding.clone())),
+                Err(_) => return None
+            },
+            _ => return None
+        }
+    }
+    Some((bindings, quoted))
+}
+
+//the let form with the resolved bindings, the body is resolved in the scope of the bindings
+fn with_bindings(list: &List, bindings: Vec<Value>, quoted: bool, frames: &mut Frames) -> List {
+    let mut cells = vec!(list.cells().first().unwrap().clone(), requote(Value::List(List::from_cells(bindings)), quoted));
+    cells.extend(list.cells()[2..].iter().map(|x| resolve_value(x, frames)));
+    List::from_cells(cells)
+}
+
+//every clause has a scope with the names of its pattern, the guard and the body are evaluated in it
+#[allow(clippy::len_zero, clippy::needless_borrowed_reference)]
+fn resolve_match(list: &List, frames: &mut Frames) -> List {
+    let cells = list.cells();
+    if cells.len() < 2 {
+        return list.clone();
+    }
+    let mut result = vec!(cells[0].clone(), resolve_value(&cells[1], frames));
+    for cell in &cells[2..] {
+        let (clause, quoted) = match unquote(cell) {
+            (&Value::List(ref clause), quoted) if clause.cells().len() > 0 => (clause, quoted),
+            _ => {
+                result.push(cell.clone());
+                continue;
+            }
+        };
+        let pattern = match Pattern::from_value(clause.cells().first().unwrap()) {
+            Ok(pattern) => pattern,
+            Err(_) => {
+                result.push(cell.clone());
+                continue;
+            }
+        };
+        let names = unique(pattern.names());
+        let scoped = names.len() > 0;
+        if scoped {
+            frames.push(names);
+        }
+        let mut clause_cells = vec!(clause.cells()[0].clone());
+        clause_cells.extend(clause.cells()[1..].iter().map(|x| resolve_value(x, frames)));
+        if scoped {
+            frames.pop();
+        }
+        result.push(requote(Value::List(List::from_cells(clause_cells)), quoted));
+    }
+    List::from_cells(result)
+}
+
+fn unique(names: Vec<Symbol>) -> Vec<Symbol> {
+    let mut result = Vec::new();
+    for name in names {
+        if !result.contains(&name) {
+            result.push(name);
+        }
+    }
+    result
+}
+
+//the quoted value of '(...) and true, other values stay unchanged
+#[allow(clippy::needless_borrowed_reference)]
+pub fn unquote(value: &Value) -> (&Value, bool) {
+    match value {
+        &Value::List(ref list) if list.cells().len() == 2 && is_quote(list.cells().first().unwrap()) => {
+            (list.cells().get(1).unwrap(), true)
+        },
+        other => (other, false)
+    }
+}
+
+//wraps the value in quote again if unquote found it quoted
+pub fn requote(value: Value, quoted: bool) -> Value {
+    match quoted {
+        true => Value::List(List::from_cells(vec!(Value::Symbol(Symbol::new("quote")), value))),
+        false => value
+    }
+}
\ No newline at end of file
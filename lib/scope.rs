@@ -1,28 +1,84 @@
+use symbol::Symbol;
 use value::Value;
 
 use std::collections::HashMap;
 
+//scopes with up to this many variables are searched linearly, larger ones get an index
+const LINEAR_LIMIT: usize = 8;
+
+//the variables are stored in slots in the order they were defined, a slot never moves,
+//so the resolver can address a variable by its slot
 #[derive(Clone, Debug)]
 pub struct Scope {
-    vars: HashMap<String, Value>
+    slots: Vec<(Symbol, Value)>,
+    index: HashMap<Symbol, usize>,
+    //the combined masks of the names, see Symbol::mask
+    mask: u64
 }
 
 impl Scope {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Scope {
         Scope {
-            vars: HashMap::new()
+            slots: Vec::new(),
+            index: HashMap::new(),
+            mask: 0
+        }
+    }
+
+    pub fn mask(&self) -> u64 {
+        self.mask
+    }
+
+    //the slot of the variable
+    pub fn position(&self, name: &Symbol) -> Option<usize> {
+        if self.mask & name.mask() == 0 {
+            None
+        }
+        else if self.slots.len() <= LINEAR_LIMIT {
+            self.slots.iter().position(|x| x.0 == *name)
+        }
+        else {
+            self.index.get(name).cloned()
         }
     }
 
-    pub fn get_variable(&self, name: &String) -> Option<&Value> {
-        self.vars.get(name)
+    pub fn slot(&self, slot: usize) -> Option<&(Symbol, Value)> {
+        self.slots.get(slot)
     }
 
-    pub fn has_variable(&self, name: &String) -> bool {
-        self.vars.contains_key(name)
+    pub fn get_variable(&self, name: &Symbol) -> Option<&Value> {
+        self.position(name).map(|x| &self.slots[x].1)
+    }
+
+    pub fn has_variable(&self, name: &Symbol) -> bool {
+        self.position(name).is_some()
+    }
+
+    //replaces the value of an existing variable or adds it in a new slot
+    #[allow(clippy::single_match)]
+    pub fn set_variable(&mut self, name: Symbol, value: Value) {
+        match self.position(&name) {
+            Some(slot) => {
+                self.slots[slot].1 = value;
+                return;
+            },
+            None => {}
+        }
+        self.mask |= name.mask();
+        self.slots.push((name, value));
+        if self.slots.len() == LINEAR_LIMIT + 1 {
+            self.index = self.slots.iter().enumerate().map(|(i, x)| (x.0.clone(), i)).collect();
+        }
+        else if self.slots.len() > LINEAR_LIMIT + 1 {
+            self.index.insert(self.slots.last().unwrap().0.clone(), self.slots.len() - 1);
+        }
     }
 
-    pub fn set_variable(&mut self, name: String, value: Value) {
-        self.vars.insert(name, value);
+    //all variables of this scope, sorted by name
+    pub fn variables(&self) -> Vec<(&Symbol, &Value)> {
+        let mut vars = self.slots.iter().map(|x| (&x.0, &x.1)).collect::<Vec<_>>();
+        vars.sort_by(|a, b| a.0.cmp(b.0));
+        vars
     }
 }
\ No newline at end of file
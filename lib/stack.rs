@@ -1,84 +1,359 @@
 use scope::Scope;
 use error::Error;
 use value::Value;
+use symbol::Symbol;
+use resolver::Variable;
+use list::List;
+use debug::DebugHook;
+use profiler::Profiler;
+use trace::Tracer;
+use testrunner::TestCase;
+
+//a scope, the level of the list it belongs to and the combined masks of the local scopes up to this one,
+//so that a name that is in none of them is read from the global scope right away
+struct Frame {
+    level: usize,
+    scope: Scope,
+    locals: u64
+}
 
-#[derive(Debug)]
 pub struct Stack {
-    scopes: Vec<Scope>
+    //sorted by level, the first one is the global scope
+    //a list that binds nothing only gets a scope when 'set' defines a variable in it
+    frames: Vec<Frame>,
+    //the number of lists that are evaluated, the global scope is level 0
+    levels: usize,
+    debug_hook: Option<Box<dyn DebugHook>>,
+    profiler: Option<Profiler>,
+    tracer: Option<Tracer>,
+    tests: Vec<TestCase>,
+    location: Option<(usize, usize)>
 }
 
 impl Stack {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Stack {
-        Stack {
-            scopes: Vec::new()
-        }
+        Stack::from_scopes(Vec::new())
     }
 
     pub fn from_scopes(scopes: Vec<Scope>) -> Stack {
-        Stack {
-            scopes: scopes
+        let mut stack = Stack {
+            levels: 0,
+            frames: Vec::new(),
+            debug_hook: None,
+            profiler: None,
+            tracer: None,
+            tests: Vec::new(),
+            location: None
+        };
+        for scope in scopes {
+            stack.push(scope);
         }
+        stack
     }
 
+    //the number of levels, including the ones without a scope
     pub fn size(&self) -> usize {
-        self.scopes.len()
+        self.levels
     }
 
+    //leaves the topmost level and removes its scope
     pub fn pop(&mut self) {
-        let _ = self.scopes.pop();
+        if self.levels == 0 {
+            return;
+        }
+        self.levels -= 1;
+        if self.frames.last().map(|x| x.level) == Some(self.levels) {
+            let _ = self.frames.pop();
+        }
     }
 
+    #[allow(clippy::redundant_field_names)]
     pub fn push(&mut self, scope: Scope) {
-        self.scopes.push(scope);
+        self.frames.push(Frame {
+            level: self.levels,
+            scope: scope,
+            locals: 0
+        });
+        self.levels += 1;
+        let index = self.frames.len() - 1;
+        self.update_masks(index);
+    }
+
+    //enters a level without creating a scope for it
+    pub fn enter(&mut self) {
+        self.levels += 1;
+    }
+
+    //removes all levels above the given size, used to clean up after errors
+    pub fn truncate(&mut self, size: usize) {
+        self.levels = self.levels.min(size);
+        while self.frames.last().map(|x| x.level >= size) == Some(true) {
+            let _ = self.frames.pop();
+        }
+    }
+
+    //the scopes that exist, levels without variables have none
+    pub fn scopes(&self) -> Vec<&Scope> {
+        self.frames.iter().map(|x| &x.scope).collect()
+    }
+
+    pub fn global(&self) -> Option<&Scope> {
+        self.frames.first().map(|x| &x.scope)
     }
 
     pub fn into_first_scope(self) -> Option<Scope> {
-        self.scopes.into_iter().next()
+        self.frames.into_iter().next().map(|x| x.scope)
     }
 
+    //the global scope is not part of the masks, so it can be changed directly
     pub fn get_mut_first(&mut self) -> Option<&mut Scope> {
-        self.scopes.get_mut(0)
+        self.frames.get_mut(0).map(|x| &mut x.scope)
     }
 
-    pub fn resolve_variable(&self, var: &String) -> Result<Value, Error> {
-        if self.size() == 0 {
-            return Err(Error::new(format!("tried to resolve variable on empty stack.")));
+    //defines the variable in the scope of the topmost level, which is created if the level has none
+    pub fn set_last_variable(&mut self, name: Symbol, value: Value) {
+        if self.levels == 0 {
+            return;
         }
-        let mut counter = self.size() - 1;
-        loop {
-            let scope = self.scopes.get(counter).unwrap();
-            if scope.has_variable(&var) {
-                return Ok(scope.get_variable(&var).unwrap().clone());
+        let level = self.levels - 1;
+        self.set_variable_at(level, name, value);
+    }
+
+    //defines the variable in the scope of the level, the scope is inserted in front of the ones of higher levels if it does not exist
+    #[allow(clippy::redundant_field_names)]
+    fn set_variable_at(&mut self, level: usize, name: Symbol, value: Value) {
+        let index = match self.frames.iter().rposition(|x| x.level <= level) {
+            Some(index) if self.frames[index].level == level => index,
+            found => {
+                let index = found.map(|x| x + 1).unwrap_or(0);
+                self.frames.insert(index, Frame {
+                    level: level,
+                    scope: Scope::new(),
+                    locals: 0
+                });
+                index
             }
-            if counter == 0 {
-                break;
+        };
+        self.frames[index].scope.set_variable(name, value);
+        self.update_masks(index);
+    }
+
+    //recomputes the combined masks from the frame at the index on
+    fn update_masks(&mut self, index: usize) {
+        for i in index.max(1)..self.frames.len() {
+            self.frames[i].locals = self.frames[i - 1].locals | self.frames[i].scope.mask();
+        }
+    }
+
+    //a single scope with all visible variables, inner variables shadow outer ones
+    pub fn flatten(&self) -> Scope {
+        let mut result = Scope::new();
+        for frame in &self.frames {
+            for (name, value) in frame.scope.variables() {
+                result.set_variable(name.clone(), value.clone());
             }
-            counter -= 1;
         }
-        Err(Error::new(format!("unknown variable '{}'.", var)))
+        result
     }
 
-    pub fn set_or_append_variable(&mut self, var: String, value: Value) -> Result<(), Error> {
-        if self.size() == 0 {
-            return Err(Error::new(format!("tried to set variable on empty stack.")));
+    pub fn set_debug_hook(&mut self, hook: Option<Box<dyn DebugHook>>) {
+        self.debug_hook = hook;
+    }
+
+    pub fn take_debug_hook(&mut self) -> Option<Box<dyn DebugHook>> {
+        self.debug_hook.take()
+    }
+
+    pub fn set_profiler(&mut self, profiler: Option<Profiler>) {
+        self.profiler = profiler;
+    }
+
+    pub fn take_profiler(&mut self) -> Option<Profiler> {
+        self.profiler.take()
+    }
+
+    #[allow(clippy::single_match)]
+    pub fn profile_enter(&mut self, name: &str) {
+        match self.profiler {
+            Some(ref mut profiler) => profiler.enter(name),
+            None => {}
         }
-        let mut counter = self.size() - 1;
-        loop {
-            let scope = self.scopes.get_mut(counter).unwrap();
-            if scope.has_variable(&var) {
-                scope.set_variable(var, value);
-                return Ok(());
+    }
+
+    #[allow(clippy::single_match)]
+    pub fn profile_leave(&mut self) {
+        match self.profiler {
+            Some(ref mut profiler) => profiler.leave(),
+            None => {}
+        }
+    }
+
+    pub fn set_tracer(&mut self, tracer: Option<Tracer>) {
+        self.tracer = tracer;
+    }
+
+    pub fn take_tracer(&mut self) -> Option<Tracer> {
+        self.tracer.take()
+    }
+
+    //creates a tracer that logs to stderr if there is none yet
+    pub fn tracer_mut(&mut self) -> &mut Tracer {
+        if self.tracer.is_none() {
+            self.tracer = Some(Tracer::to_stderr());
+        }
+        self.tracer.as_mut().unwrap()
+    }
+
+    pub fn trace_enter(&mut self, name: &str, args: &[Value], evaluated: bool) -> bool {
+        match self.tracer {
+            Some(ref mut tracer) => tracer.enter(name, args, evaluated),
+            None => false
+        }
+    }
+
+    #[allow(clippy::single_match)]
+    pub fn trace_leave(&mut self, traced: bool, name: &str, result: &Result<Value, Error>) {
+        match self.tracer {
+            Some(ref mut tracer) => tracer.leave(traced, name, result),
+            None => {}
+        }
+    }
+
+    pub fn add_test(&mut self, test: TestCase) {
+        self.tests.push(test);
+    }
+
+    pub fn take_tests(&mut self) -> Vec<TestCase> {
+        ::std::mem::take(&mut self.tests)
+    }
+
+    //the first and last line of the top level form that is currently evaluated
+    pub fn location(&self) -> Option<(usize, usize)> {
+        self.location
+    }
+
+    pub fn set_location(&mut self, location: Option<(usize, usize)>) {
+        self.location = location;
+    }
+
+    //the hook is taken out of the stack while it runs, so code evaluated by the hook is not debugged
+    pub fn debug_before_eval(&mut self, list: &List) -> Result<(), Error> {
+        match self.debug_hook.take() {
+            Some(mut hook) => {
+                let result = hook.before_eval(list, self);
+                self.debug_hook = Some(hook);
+                result
+            },
+            None => Ok(())
+        }
+    }
+
+    pub fn debug_after_eval(&mut self, list: &List, result: &Result<Value, Error>) -> Result<(), Error> {
+        match self.debug_hook.take() {
+            Some(mut hook) => {
+                let hook_result = hook.after_eval(list, result, self);
+                self.debug_hook = Some(hook);
+                hook_result
+            },
+            None => Ok(())
+        }
+    }
+
+    pub fn debug_break(&mut self, list: &List) -> Result<(), Error> {
+        match self.debug_hook.take() {
+            Some(mut hook) => {
+                let result = hook.on_break(list, self);
+                self.debug_hook = Some(hook);
+                result
+            },
+            None => Ok(())
+        }
+    }
+
+    #[allow(clippy::single_match, clippy::useless_format)]
+    pub fn resolve_variable(&self, var: &Symbol) -> Result<Value, Error> {
+        let global = match self.frames.first() {
+            Some(frame) => &frame.scope,
+            None => return Err(Error::new(format!("tried to resolve variable on empty stack.")))
+        };
+        match self.local_variable(var) {
+            Some(value) => return Ok(value.clone()),
+            None => {}
+        }
+        match global.get_variable(var) {
+            Some(value) => Ok(value.clone()),
+            None => Err(Error::new(format!("unknown variable '{}'.", var)))
+        }
+    }
+
+    //resolves a variable of the resolver: its address is used if the scope still has the variable in that slot,
+    //otherwise the local scopes are searched and the global scope is read at the cached slot
+    #[allow(clippy::needless_borrowed_reference, clippy::single_match, clippy::useless_format)]
+    pub fn lookup(&self, var: &Variable) -> Result<Value, Error> {
+        let global = match self.frames.first() {
+            Some(frame) => &frame.scope,
+            None => return Err(Error::new(format!("tried to resolve variable on empty stack.")))
+        };
+        match var.address() {
+            Some((depth, slot)) if depth + 1 < self.frames.len() => {
+                match self.frames[self.frames.len() - 1 - depth].scope.slot(slot) {
+                    Some(&(ref name, ref value)) if name == var.symbol() => return Ok(value.clone()),
+                    _ => {}
+                }
+            },
+            _ => {}
+        }
+        match self.local_variable(var.symbol()) {
+            Some(value) => return Ok(value.clone()),
+            None => {}
+        }
+        match global.slot(var.global_slot()) {
+            Some(&(ref name, ref value)) if name == var.symbol() => return Ok(value.clone()),
+            _ => {}
+        }
+        match global.position(var.symbol()) {
+            Some(slot) => {
+                var.set_global_slot(slot);
+                Ok(global.slot(slot).unwrap().1.clone())
+            },
+            None => Err(Error::new(format!("unknown variable '{}'.", var.symbol())))
+        }
+    }
+
+    //the value of the variable in the innermost local scope that has it
+    #[allow(clippy::single_match)]
+    fn local_variable(&self, var: &Symbol) -> Option<&Value> {
+        let mask = var.mask();
+        for frame in self.frames.iter().skip(1).rev() {
+            if frame.locals & mask == 0 {
+                return None;
             }
-            if counter == 0 {
-                break;
+            match frame.scope.get_variable(var) {
+                Some(value) => return Some(value),
+                None => {}
             }
-            counter -= 1;
         }
-        if self.size() == 1 {
+        None
+    }
+
+    #[allow(clippy::single_match, clippy::useless_format)]
+    pub fn set_or_append_variable(&mut self, var: Symbol, value: Value) -> Result<(), Error> {
+        if self.levels == 0 {
+            return Err(Error::new(format!("tried to set variable on empty stack.")));
+        }
+        match self.frames.iter().rposition(|x| x.scope.has_variable(&var)) {
+            Some(index) => {
+                self.frames[index].scope.set_variable(var, value);
+                return Ok(());
+            },
+            None => {}
+        }
+        if self.levels == 1 {
             return Err(Error::new(format!("no scope above the current one.")));
         }
-        let index = self.size() - 2;
-        self.scopes.get_mut(index).unwrap().set_variable(var, value);
+        let level = self.levels - 2;
+        self.set_variable_at(level, var, value);
         Ok(())
     }
 }
\ No newline at end of file
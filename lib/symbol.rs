@@ -0,0 +1,146 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt::{
+    Display,
+    Debug
+};
+use std::fmt::Result as FmtResult;
+use std::fmt::Formatter;
+use std::hash::{
+    Hash,
+    Hasher
+};
+use std::ops::Deref;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::OnceLock;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering as AtomicOrdering;
+
+//an interned name: all symbols with the same name share one string, so comparing and hashing
+//a symbol only looks at the pointer, the table is shared by all threads and never shrinks
+#[derive(Clone)]
+pub struct Symbol {
+    name: Arc<str>
+}
+
+fn table() -> &'static Mutex<HashSet<Arc<str>>> {
+    static TABLE: OnceLock<Mutex<HashSet<Arc<str>>>> = OnceLock::new();
+    TABLE.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+impl Symbol {
+    pub fn new(name: &str) -> Symbol {
+        let mut table = table().lock().unwrap();
+        match table.get(name) {
+            Some(interned) => Symbol {
+                name: interned.clone()
+            },
+            None => {
+                let interned: Arc<str> = Arc::from(name);
+                table.insert(interned.clone());
+                Symbol {
+                    name: interned
+                }
+            }
+        }
+    }
+
+    //a symbol with a name that was not used before, the prefix followed by a number
+    pub fn gensym(prefix: &str) -> Symbol {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let mut table = table().lock().unwrap();
+        loop {
+            let name = format!("{}{}", prefix, COUNTER.fetch_add(1, AtomicOrdering::Relaxed));
+            if !table.contains(&name[..]) {
+                let interned: Arc<str> = Arc::from(name);
+                table.insert(interned.clone());
+                return Symbol {
+                    name: interned
+                };
+            }
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+
+    //one of 64 bits chosen by the address of the name, a scope combines the bits of its variables,
+    //so that a scope without the bit of a symbol does not need to be searched for it
+    pub fn mask(&self) -> u64 {
+        let address = self.name.as_ptr() as usize;
+        1 << ((address >> 3 ^ address >> 9) & 63)
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<'a> From<&'a str> for Symbol {
+    fn from(name: &'a str) -> Symbol {
+        Symbol::new(name)
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(name: String) -> Symbol {
+        Symbol::new(&name)
+    }
+}
+
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Symbol) -> bool {
+        Arc::ptr_eq(&self.name, &other.name)
+    }
+}
+
+impl Eq for Symbol {}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        &*self.name == other
+    }
+}
+
+impl<'a> PartialEq<&'a str> for Symbol {
+    fn eq(&self, other: &&'a str) -> bool {
+        &*self.name == *other
+    }
+}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.name.as_ptr() as usize).hash(state);
+    }
+}
+
+//symbols are ordered by their names
+impl PartialOrd for Symbol {
+    fn partial_cmp(&self, other: &Symbol) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Symbol {
+    fn cmp(&self, other: &Symbol) -> Ordering {
+        self.name.cmp(&other.name)
+    }
+}
+
+impl Display for Symbol {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        Display::fmt(&*self.name, f)
+    }
+}
+
+impl Debug for Symbol {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        Debug::fmt(&*self.name, f)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,183 @@
+use std::fs;
+use std::io::Result as IOResult;
+use std::path::Path;
+use std::time::{
+    Duration,
+    Instant
+};
+
+use interpreter::Interpreter;
+use value::Value;
+
+//a test that was registered by 'deftest'
+#[derive(Debug, Clone)]
+pub struct TestCase {
+    pub name: String,
+    pub body: Vec<Value>
+}
+
+#[derive(Debug)]
+pub struct TestResult {
+    pub file: String,
+    pub name: String,
+    pub failure: Option<String>,
+    pub duration: Duration
+}
+
+impl TestResult {
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Plain,
+    Tap,
+    JUnit
+}
+
+//finds all files ending with '_test.ali' in the given files and directories
+pub fn discover(paths: &[String]) -> IOResult<Vec<String>> {
+    let mut files = Vec::new();
+    for path in paths {
+        discover_path(Path::new(path), true, &mut files)?;
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+fn discover_path(path: &Path, explicit: bool, files: &mut Vec<String>) -> IOResult<()> {
+    let name = path.file_name().map(|x| x.to_string_lossy().into_owned()).unwrap_or_default();
+    if path.is_dir() {
+        //hidden directories and build output are skipped unless they were given explicitly
+        if !explicit && (name.starts_with('.') || name == "target") {
+            return Ok(());
+        }
+        for entry in fs::read_dir(path)? {
+            discover_path(&entry?.path(), false, files)?;
+        }
+    }
+    else if explicit || name.ends_with("_test.ali") {
+        files.push(path.to_string_lossy().into_owned());
+    }
+    Ok(())
+}
+
+//loads the file and runs each of its tests in a fresh interpreter that contains the definitions of the file
+#[allow(clippy::redundant_field_names)]
+pub fn run_file(path: &str, filter: Option<&str>) -> IOResult<Vec<TestResult>> {
+    let mut interpreter = Interpreter::new();
+    interpreter.load_script(path.to_owned())?;
+    let tests = interpreter.take_tests();
+    let mut results = Vec::new();
+    for test in tests {
+        match filter {
+            Some(pattern) if !test.name.contains(pattern) => continue,
+            _ => {}
+        }
+        let mut test_interpreter = Interpreter::from_global(interpreter.global_scope().clone());
+        let start = Instant::now();
+        let mut failure = None;
+        for elem in test.body {
+            match test_interpreter.eval_value(elem) {
+                Ok(_) => {},
+                Err(err) => {
+                    failure = Some(format!("{}", err).trim_end().to_owned());
+                    break;
+                }
+            }
+        }
+        results.push(TestResult {
+            file: path.to_owned(),
+            name: test.name,
+            failure: failure,
+            duration: start.elapsed()
+        });
+    }
+    Ok(results)
+}
+
+pub fn format_results(results: &[TestResult], format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Plain => format_plain(results),
+        OutputFormat::Tap => format_tap(results),
+        OutputFormat::JUnit => format_junit(results)
+    }
+}
+
+fn format_plain(results: &[TestResult]) -> String {
+    let mut buffer = String::new();
+    let mut file = "";
+    for result in results {
+        if result.file != file {
+            file = &result.file;
+            buffer.push_str(&format!("{}\n", file));
+        }
+        match result.failure {
+            None => buffer.push_str(&format!("    ok    {}\n", result.name)),
+            Some(ref failure) => {
+                buffer.push_str(&format!("    FAIL  {}\n", result.name));
+                for line in failure.lines() {
+                    buffer.push_str(&format!("          {}\n", line));
+                }
+            }
+        }
+    }
+    let failed = results.iter().filter(|x| !x.passed()).count();
+    buffer.push_str(&format!("\n{} passed, {} failed\n", results.len() - failed, failed));
+    buffer
+}
+
+//the test anything protocol, version 13
+fn format_tap(results: &[TestResult]) -> String {
+    let mut buffer = format!("TAP version 13\n1..{}\n", results.len());
+    for (i, result) in results.iter().enumerate() {
+        match result.failure {
+            None => buffer.push_str(&format!("ok {} - {}: {}\n", i + 1, result.file, result.name)),
+            Some(ref failure) => {
+                buffer.push_str(&format!("not ok {} - {}: {}\n", i + 1, result.file, result.name));
+                buffer.push_str("  ---\n  message: |\n");
+                for line in failure.lines() {
+                    buffer.push_str(&format!("    {}\n", line));
+                }
+                buffer.push_str("  ...\n");
+            }
+        }
+    }
+    buffer
+}
+
+fn format_junit(results: &[TestResult]) -> String {
+    let failed = results.iter().filter(|x| !x.passed()).count();
+    let mut buffer = format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites tests=\"{}\" failures=\"{}\">\n", results.len(), failed);
+    let mut files = results.iter().map(|x| &x.file[..]).collect::<Vec<&str>>();
+    files.dedup();
+    for file in files {
+        let suite = results.iter().filter(|x| x.file == file).collect::<Vec<_>>();
+        let suite_failed = suite.iter().filter(|x| !x.passed()).count();
+        let time = suite.iter().map(|x| x.duration.as_secs_f64()).sum::<f64>();
+        buffer.push_str(&format!("  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{:.6}\">\n", escape_xml(file), suite.len(), suite_failed, time));
+        for result in suite {
+            buffer.push_str(&format!("    <testcase name=\"{}\" classname=\"{}\" time=\"{:.6}\"", escape_xml(&result.name), escape_xml(file), result.duration.as_secs_f64()));
+            match result.failure {
+                None => buffer.push_str("/>\n"),
+                Some(ref failure) => {
+                    let message = failure.lines().next().unwrap_or("");
+                    buffer.push_str(&format!(">\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n", escape_xml(message), escape_xml(failure)));
+                }
+            }
+        }
+        buffer.push_str("  </testsuite>\n");
+    }
+    buffer.push_str("</testsuites>\n");
+    buffer
+}
+
+fn escape_xml(string: &str) -> String {
+    string.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
\ No newline at end of file
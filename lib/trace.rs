@@ -0,0 +1,112 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{
+    stderr,
+    Write
+};
+use std::io::Result as IOResult;
+
+use error::Error;
+use value::Value;
+
+//logs function calls with their arguments and results, indented by the depth of traced calls
+pub struct Tracer {
+    all: bool,
+    traced: HashSet<String>,
+    ignored: HashSet<String>,
+    depth: usize,
+    out: Box<dyn Write>
+}
+
+impl Tracer {
+    //a tracer that does not trace anything until names are added with 'trace'
+    #[allow(clippy::redundant_field_names)]
+    pub fn new(out: Box<dyn Write>) -> Tracer {
+        Tracer {
+            all: false,
+            traced: HashSet::new(),
+            ignored: HashSet::new(),
+            depth: 0,
+            out: out
+        }
+    }
+
+    pub fn to_stderr() -> Tracer {
+        Tracer::new(Box::new(stderr()))
+    }
+
+    pub fn to_file(path: &str) -> IOResult<Tracer> {
+        Ok(Tracer::new(Box::new(File::create(path)?)))
+    }
+
+    pub fn trace_all(&mut self) {
+        self.all = true;
+        self.ignored.clear();
+    }
+
+    pub fn trace(&mut self, name: String) {
+        self.ignored.remove(&name);
+        self.traced.insert(name);
+    }
+
+    pub fn untrace(&mut self, name: String) {
+        self.traced.remove(&name);
+        self.ignored.insert(name);
+    }
+
+    pub fn untrace_all(&mut self) {
+        self.all = false;
+        self.traced.clear();
+        self.ignored.clear();
+    }
+
+    pub fn is_traced(&self, name: &str) -> bool {
+        self.traced.contains(name) || (self.all && !self.ignored.contains(name))
+    }
+
+    //returns whether the call was logged, which has to be passed on to leave
+    //builtins get the forms of their arguments before they evaluate them, their lines are marked as unevaluated
+    pub fn enter(&mut self, name: &str, args: &[Value], evaluated: bool) -> bool {
+        if !self.is_traced(name) {
+            return false;
+        }
+        let mut call = format!("({}", name);
+        for arg in args {
+            call.push_str(&format!(" {}", arg));
+        }
+        call.push(')');
+        if !evaluated {
+            call.push_str(" [unevaluated]");
+        }
+        let line = format!("{}> {}", self.indent(), call);
+        self.write_line(line);
+        self.depth += 1;
+        true
+    }
+
+    #[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference)]
+    pub fn leave(&mut self, traced: bool, name: &str, result: &Result<Value, Error>) {
+        if !traced {
+            return;
+        }
+        if self.depth > 0 {
+            self.depth -= 1;
+        }
+        let line = match result {
+            &Ok(ref value) => format!("{}< {} = {}", self.indent(), name, value),
+            &Err(ref err) => {
+                let message = format!("{}", err);
+                format!("{}< {} ! {}", self.indent(), name, message.lines().next().unwrap_or(""))
+            }
+        };
+        self.write_line(line);
+    }
+
+    fn indent(&self) -> String {
+        "  ".repeat(self.depth)
+    }
+
+    fn write_line(&mut self, line: String) {
+        let _ = writeln!(self.out, "{}", line);
+    }
+}
\ No newline at end of file
@@ -4,12 +4,25 @@ use std::fmt::{
 };
 use std::fmt::Result as FmtResult;
 use std::fmt::Formatter;
+use std::str::FromStr;
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
 
 use ::FLOAT;
 use ::INT;
+use atom::Atom;
 use error::Error;
+use future::Future;
 use list::List;
 use lambda::Lambda;
+use lazy::LazySeq;
+use map::Map;
+use record::Record;
+use symbol::Symbol;
+use resolver::Variable;
+use number::big_to_value;
+use number::ratio_to_value;
 
 const NUMBER_CHARS: [char; 14] = ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '.', '+', '-', 'e'];
 
@@ -19,10 +32,22 @@ pub enum Value {
     List(List),
     Float(FLOAT),
     Integer(INT),
-    Symbol(String),
+    //an integer that does not fit into INT, smaller values are always stored as Integer
+    BigInt(BigInt),
+    //an exact fraction, ratios with a denominator of one are always stored as integers
+    Ratio(BigRational),
+    Symbol(Symbol),
+    //a symbol in a lambda body that was rewritten by the resolver, it is never the result of an evaluation
+    Variable(Variable),
     Lambda(Lambda),
     Boolean(bool),
-    Char(char)
+    Char(char),
+    Map(Map),
+    Atom(Atom),
+    //the result of pmap, future or promise, it is read with await
+    Future(Future),
+    Record(Record),
+    LazySeq(LazySeq)
 }
 
 impl Value {
@@ -36,6 +61,13 @@ impl Value {
         else if code == "false" {
             Value::Boolean(false)
         }
+        else if code.contains('_') && starts_with_digit(&code) {
+            //digit separators like in 1_000_000
+            match Value::from_string(code.replace('_', "")) {
+                Value::Symbol(_) => Value::Symbol(Symbol::from(code)),
+                value => value
+            }
+        }
         else if is_numeric(&code) {
             let dot_count = code.chars().filter(|x| *x == '.').count();
             if dot_count == 1 {
@@ -44,7 +76,7 @@ impl Value {
                         Value::Float(float)
                     },
                     Err(_) => {
-                        Value::Symbol(code)
+                        Value::Symbol(Symbol::from(code))
                     }
                 }
             }
@@ -54,19 +86,32 @@ impl Value {
                         Value::Integer(int)
                     },
                     Err(_) => {
-                        Value::Symbol(code)
+                        match BigInt::from_str(&code) {
+                            Ok(big) => Value::BigInt(big),
+                            Err(_) => Value::Symbol(Symbol::from(code))
+                        }
                     }
                 }
             }
             else {
-                Value::Symbol(code)
+                Value::Symbol(Symbol::from(code))
             }
         }
+        else if is_ratio(&code) {
+            let mut split = code.split('/');
+            let numerator = BigInt::from_str(split.next().unwrap()).unwrap();
+            let denominator = BigInt::from_str(split.next().unwrap()).unwrap();
+            ratio_to_value(BigRational::new(numerator, denominator))
+        }
         else {
-            Value::Symbol(code)
+            match parse_radix(&code) {
+                Some(value) => value,
+                None => Value::Symbol(Symbol::from(code))
+            }
         }   
     }
 
+    #[allow(clippy::needless_return)]
     pub fn char_from_string(string: &str) -> Result<Value, Error> {
         if string.starts_with('\\') {
             let ch = match string {
@@ -93,7 +138,32 @@ impl Value {
     pub fn new_lambda(lambda: Lambda) -> Value {
         Value::Lambda(lambda)
     }
+
+    //a string is a list that only contains characters
+    #[allow(clippy::redundant_closure)]
+    pub fn new_string(string: &str) -> Value {
+        Value::List(List::from_cells(string.chars().map(|x| Value::Char(x)).collect()))
+    }
+
+    //the content of a string, or None if the value is not a string
+    #[allow(clippy::needless_borrowed_reference)]
+    pub fn string_content(&self) -> Option<String> {
+        match self {
+            &Value::List(ref list) => {
+                let mut string = String::new();
+                for elem in list.cells() {
+                    match elem {
+                        &Value::Char(ch) => string.push(ch),
+                        _ => return None
+                    }
+                }
+                Some(string)
+            },
+            _ => None
+        }
+    }
     
+    #[allow(clippy::needless_borrowed_reference)]
     pub fn is_list_and_string(&self) -> bool {
         match self {
             &Value::List(ref list) => {
@@ -112,26 +182,35 @@ impl Value {
         }
     }
 
-    pub fn type_str(&self) -> &'static str {
+    pub fn type_str(&self) -> &str {
         match *self {
             Value::Nil => "nil",
             Value::List(_) => "list",
             Value::Float(_) => "float",
             Value::Integer(_) => "int",
+            Value::BigInt(_) => "int",
+            Value::Ratio(_) => "ratio",
             Value::Symbol(_) => "symbol",
+            Value::Variable(_) => "symbol",
             Value::Lambda(_) => "lambda",
             Value::Boolean(_) => "boolean",
-            Value::Char(_) => "char"
+            Value::Char(_) => "char",
+            Value::Map(_) => "map",
+            Value::Atom(_) => "atom",
+            Value::Future(_) => "future",
+            Value::Record(ref record) => record.name(),
+            Value::LazySeq(_) => "lazy-seq"
         }
     }
 
     pub fn type_value(&self) -> Value {
         let string = self.type_str();
-        Value::Symbol(string.to_owned())
+        Value::Symbol(Symbol::new(string))
     }
 }
 
 impl Debug for Value {
+    #[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference, clippy::useless_format)]
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
         match self {
             &Value::Nil => {
@@ -159,9 +238,18 @@ impl Debug for Value {
             &Value::Integer(ref int) => {
                 write!(f, "{} [int]", int)
             },
+            &Value::BigInt(ref big) => {
+                write!(f, "{} [int]", big)
+            },
+            &Value::Ratio(ref ratio) => {
+                write!(f, "{} [ratio]", ratio)
+            },
             &Value::Symbol(ref symbol) => {
                 write!(f, "{} [symbol]", symbol)
             },
+            &Value::Variable(ref variable) => {
+                write!(f, "{:?} [symbol]", variable)
+            },
             &Value::Lambda(_) => {
                 write!(f, "[lambda]")
             },
@@ -170,12 +258,30 @@ impl Debug for Value {
             },
             &Value::Char(ref ch) => {
                 write!(f, "{} [char]", ch)
+            },
+            &Value::Map(ref map) => {
+                let entries = map.entries().iter().map(|x| format!("{:?} {:?}", x.0, x.1)).collect::<Vec<_>>();
+                write!(f, "#{{{}}} [map]", entries.join(", "))
+            },
+            &Value::Atom(ref atom) => {
+                write!(f, "#atom({:?}) [atom]", atom.get())
+            },
+            &Value::Future(_) => {
+                write!(f, "[future]")
+            },
+            &Value::Record(ref record) => {
+                let fields = record.fields().iter().map(|x| format!("{} {:?}", x.0, x.1)).collect::<Vec<_>>();
+                write!(f, "#{}{{{}}} [{}]", record.name(), fields.join(" "), record.name())
+            },
+            &Value::LazySeq(_) => {
+                write!(f, "[lazy-seq]")
             }
         }
     }
 }
 
 impl Display for Value {
+    #[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference, clippy::useless_format)]
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
         match self {
             &Value::Nil => {
@@ -203,9 +309,18 @@ impl Display for Value {
             &Value::Integer(ref int) => {
                 write!(f, "{}", int)
             },
+            &Value::BigInt(ref big) => {
+                write!(f, "{}", big)
+            },
+            &Value::Ratio(ref ratio) => {
+                write!(f, "{}", ratio)
+            },
             &Value::Symbol(ref symbol) => {
                 write!(f, "{}", symbol)
             },
+            &Value::Variable(ref variable) => {
+                write!(f, "{}", variable.symbol())
+            },
             &Value::Lambda(_) => {
                 write!(f, "[lambda]")
             },
@@ -214,11 +329,70 @@ impl Display for Value {
             },
             &Value::Char(ref ch) => {
                 write!(f, "{}", ch)
+            },
+            &Value::Map(ref map) => {
+                let entries = map.entries().iter().map(|x| format!("{} {}", x.0, x.1)).collect::<Vec<_>>();
+                write!(f, "#{{{}}}", entries.join(", "))
+            },
+            &Value::Atom(ref atom) => {
+                write!(f, "#atom({})", atom.get())
+            },
+            &Value::Future(_) => {
+                write!(f, "[future]")
+            },
+            &Value::Record(ref record) => {
+                let fields = record.fields().iter().map(|x| format!("{} {}", x.0, x.1)).collect::<Vec<_>>();
+                write!(f, "#{}{{{}}}", record.name(), fields.join(" "))
+            },
+            &Value::LazySeq(_) => {
+                write!(f, "[lazy-seq]")
             }
         }
     }
 }
 
+#[allow(clippy::ptr_arg)]
 fn is_numeric(string: &String) -> bool {
     string.chars().filter(|x| !NUMBER_CHARS.contains(x)).count() == 0
+}
+
+//a literal like 2/3 or -1/2, the denominator must not be zero
+#[allow(clippy::len_zero, clippy::ptr_arg)]
+fn is_ratio(string: &String) -> bool {
+    let split = string.split('/').collect::<Vec<&str>>();
+    if split.len() != 2 {
+        return false;
+    }
+    let numerator = split[0].strip_prefix('-').or(split[0].strip_prefix('+')).unwrap_or(split[0]);
+    let is_digits = |x: &str| x.len() > 0 && x.chars().all(|ch| ch.is_ascii_digit());
+    is_digits(numerator) && is_digits(split[1]) && split[1].chars().any(|ch| ch != '0')
+}
+
+fn strip_sign(string: &str) -> (bool, &str) {
+    match string.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, string.strip_prefix('+').unwrap_or(string))
+    }
+}
+
+fn starts_with_digit(string: &str) -> bool {
+    strip_sign(string).1.chars().next().map(|x| x.is_ascii_digit()).unwrap_or(false)
+}
+
+//hexadecimal, octal and binary literals like 0xff, 0o17 or -0b101
+#[allow(clippy::len_zero)]
+fn parse_radix(string: &str) -> Option<Value> {
+    let (negative, rest) = strip_sign(string);
+    let radix = match rest.get(0..2) {
+        Some("0x") => 16,
+        Some("0o") => 8,
+        Some("0b") => 2,
+        _ => return None
+    };
+    let digits = &rest[2..];
+    if digits.len() == 0 || !digits.chars().all(|x| x.is_digit(radix)) {
+        return None;
+    }
+    let value = BigInt::parse_bytes(digits.as_bytes(), radix)?;
+    Some(big_to_value(if negative { -value } else { value }))
 }
\ No newline at end of file
@@ -5,18 +5,64 @@ use rustyline::error::ReadlineError;
 use rustyline::Editor;
 
 use alisplib::interpreter::Interpreter;
+use alisplib::debug::Debugger;
+use alisplib::profiler::Profiler;
+use alisplib::trace::Tracer;
+use alisplib::pool;
+use alisplib::testrunner;
+use alisplib::testrunner::OutputFormat;
 
 use std::env;
+use std::fs::File;
+use std::io::Write;
 use std::io::Result as IOResult;
+use std::process;
 
+//the command line flags that change how the interpreter runs
+struct Options {
+    debug: bool,
+    profile: bool,
+    profile_collapsed: Option<String>,
+    trace: bool,
+    trace_file: Option<String>,
+    trace_filter: Option<String>
+}
+
+#[allow(clippy::len_zero, clippy::redundant_field_names)]
 fn main() {
-    let args = env::args().skip(1).collect::<Vec<String>>();
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    if args.first().map(|x| &x[..]) == Some("test") {
+        args.remove(0);
+        process::exit(run_tests(args));
+    }
+    let profile_collapsed = take_option(&mut args, "--profile-collapsed");
+    let trace_file = take_option(&mut args, "--trace-file");
+    let trace_filter = take_option(&mut args, "--trace-filter");
+    match take_option(&mut args, "--threads").map(|x| x.parse::<usize>()) {
+        None => {},
+        Some(Ok(threads)) if threads > 0 => if let Err(err) = pool::set_size(threads) {
+            print!("{}", err);
+            process::exit(2);
+        },
+        Some(_) => {
+            println!("--threads expects a positive number.");
+            process::exit(2);
+        }
+    }
+    let options = Options {
+        debug: take_flag(&mut args, "--debug"),
+        profile: take_flag(&mut args, "--profile") || profile_collapsed.is_some(),
+        profile_collapsed: profile_collapsed,
+        trace: take_flag(&mut args, "--trace") || trace_file.is_some() || trace_filter.is_some(),
+        trace_file: trace_file,
+        trace_filter: trace_filter
+    };
     if args.len() == 0 {
-        start_interactive();
+        start_interactive(&options);
     }
     else {
         for file in args {
-            match start_script(file) {
+            match start_script(file, &options) {
                 Ok(_) => {},
                 Err(e) => {
                     println!("file error: {}", e);
@@ -26,23 +72,141 @@ fn main() {
     }
 }
 
-fn start_script(path: String) -> IOResult<()> {
+//usage: ali test [paths...] [--filter name] [--format plain|tap|junit]
+//returns the exit code, which is 1 if any test failed
+#[allow(clippy::len_zero, clippy::useless_format)]
+fn run_tests(mut args: Vec<String>) -> i32 {
+    let filter = take_option(&mut args, "--filter");
+    let format = match take_option(&mut args, "--format") {
+        None => OutputFormat::Plain,
+        Some(format) => match &format[..] {
+            "plain" => OutputFormat::Plain,
+            "tap" => OutputFormat::Tap,
+            "junit" => OutputFormat::JUnit,
+            unknown => {
+                println!("unknown test output format '{}', expected plain, tap or junit.", unknown);
+                return 2;
+            }
+        }
+    };
+    if args.len() == 0 {
+        args.push(format!("."));
+    }
+    let files = match testrunner::discover(&args) {
+        Ok(files) => files,
+        Err(e) => {
+            println!("file error: {}", e);
+            return 2;
+        }
+    };
+    let mut results = Vec::new();
+    for file in files {
+        match testrunner::run_file(&file, filter.as_ref().map(|x| &x[..])) {
+            Ok(mut file_results) => results.append(&mut file_results),
+            Err(e) => {
+                println!("file error in '{}': {}", file, e);
+                return 2;
+            }
+        }
+    }
+    print!("{}", testrunner::format_results(&results, format));
+    if results.iter().all(|x| x.passed()) {
+        0
+    }
+    else {
+        1
+    }
+}
+
+fn start_script(path: String, options: &Options) -> IOResult<()> {
+    let mut interpreter = new_interpreter(options);
+    interpreter.load_script(path)?;
+    finish(interpreter, options)
+}
+
+//the debugger, profiler and tracer are installed after the std was loaded, so they ignore it
+fn new_interpreter(options: &Options) -> Interpreter {
     let mut interpreter = Interpreter::new();
-    interpreter.load_script(path)
+    if options.debug {
+        interpreter.set_debug_hook(Box::new(Debugger::new()));
+    }
+    if options.profile {
+        interpreter.set_profiler(Profiler::new());
+    }
+    if options.trace {
+        let mut tracer = match options.trace_file {
+            Some(ref path) => match Tracer::to_file(path) {
+                Ok(tracer) => tracer,
+                Err(err) => {
+                    println!("file error: {}", err);
+                    Tracer::to_stderr()
+                }
+            },
+            None => Tracer::to_stderr()
+        };
+        match options.trace_filter {
+            Some(ref names) => {
+                for name in names.split(',') {
+                    tracer.trace(name.trim().to_owned());
+                }
+            },
+            None => tracer.trace_all()
+        }
+        interpreter.set_tracer(tracer);
+    }
+    interpreter
+}
+
+//prints the profiler results, if there are any
+#[allow(clippy::single_match)]
+fn finish(mut interpreter: Interpreter, options: &Options) -> IOResult<()> {
+    let profiler = match interpreter.take_profiler() {
+        Some(profiler) => profiler,
+        None => return Ok(())
+    };
+    println!();
+    print!("{}", profiler.table());
+    match options.profile_collapsed {
+        Some(ref path) => {
+            let mut file = File::create(path)?;
+            file.write_all(profiler.collapsed().as_bytes())?;
+        },
+        None => {}
+    }
+    Ok(())
+}
+
+//removes all occurrences of the flag and returns whether it was given
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let len = args.len();
+    args.retain(|x| x != flag);
+    args.len() != len
+}
+
+//removes the option and its value from the arguments and returns the value
+fn take_option(args: &mut Vec<String>, option: &str) -> Option<String> {
+    let index = args.iter().position(|x| x == option)?;
+    args.remove(index);
+    if index < args.len() {
+        Some(args.remove(index))
+    }
+    else {
+        None
+    }
 }
 
 //TODO: ignore strings when deciding between ... and >>>
 
-fn start_interactive() {
+fn start_interactive(options: &Options) {
     let mut rl = Editor::<()>::new();
-    let mut interpreter = Interpreter::new();
+    let mut interpreter = new_interpreter(options);
     println!("== A Lisp Interpreter ==");
     println!("-- under construction --");
     println!();
     loop {
         let (line, quit) = unwrap_readline(rl.readline(">>> "));
         if quit {
-            return;
+            return end_interactive(interpreter, options);
         }
         if unclosed_brackets(&line) == 0 {
             rl.add_history_entry(&line);
@@ -54,7 +218,7 @@ fn start_interactive() {
             while unclosed_brackets(&buffer) != 0 {
                 let (inner_line, quit) = unwrap_readline(rl.readline("... "));
                 if quit {
-                    return;
+                    return end_interactive(interpreter, options);
                 }
                 buffer.push_str(&inner_line);
                 buffer.push(' ');
@@ -65,6 +229,13 @@ fn start_interactive() {
     }
 }
 
+fn end_interactive(interpreter: Interpreter, options: &Options) {
+    match finish(interpreter, options) {
+        Ok(_) => {},
+        Err(e) => println!("file error: {}", e)
+    }
+}
+
 fn eval(interpreter: &mut Interpreter, code: String) {
     println!();
     match interpreter.eval_string(code) {
@@ -95,6 +266,7 @@ fn unwrap_readline(line: Result<String, ReadlineError>) -> (String, bool) {
     }
 }
 
+#[allow(clippy::ptr_arg)]
 fn unclosed_brackets(string: &String) -> isize {
     let mut acc = 0;
     let mut quoted = false;
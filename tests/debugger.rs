@@ -0,0 +1,141 @@
+extern crate alisplib;
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::process::Command;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use alisplib::debug::DebugHook;
+use alisplib::error::Error;
+use alisplib::interpreter::Interpreter;
+use alisplib::list::List;
+use alisplib::stack::Stack;
+use alisplib::value::Value;
+
+//records the calls of the hook, the lists are stored as the names of their functions
+#[derive(Default)]
+struct Events {
+    before: Vec<String>,
+    after: Vec<String>,
+    breaks: usize
+}
+
+struct Recorder {
+    events: Arc<Mutex<Events>>,
+    //on_break returns an error, which aborts the evaluation
+    abort: bool
+}
+
+impl DebugHook for Recorder {
+    fn before_eval(&mut self, list: &List, _stack: &mut Stack) -> Result<(), Error> {
+        self.events.lock().unwrap().before.push(name(list));
+        Ok(())
+    }
+
+    #[allow(clippy::match_ref_pats, clippy::needless_borrowed_reference, clippy::useless_format)]
+    fn after_eval(&mut self, list: &List, result: &Result<Value, Error>, _stack: &mut Stack) -> Result<(), Error> {
+        let result = match result {
+            &Ok(ref value) => format!("{}", value),
+            &Err(_) => format!("error")
+        };
+        self.events.lock().unwrap().after.push(format!("{} = {}", name(list), result));
+        Ok(())
+    }
+
+    #[allow(clippy::useless_format)]
+    fn on_break(&mut self, _list: &List, _stack: &mut Stack) -> Result<(), Error> {
+        self.events.lock().unwrap().breaks += 1;
+        match self.abort {
+            true => Err(Error::new(format!("aborted"))),
+            false => Ok(())
+        }
+    }
+}
+
+fn name(list: &List) -> String {
+    match list.cells().first() {
+        Some(value) => format!("{}", value),
+        None => String::new()
+    }
+}
+
+#[allow(clippy::redundant_field_names)]
+fn recorder(abort: bool) -> (Interpreter, Arc<Mutex<Events>>) {
+    let events = Arc::new(Mutex::new(Events::default()));
+    let mut interpreter = Interpreter::new_empty();
+    interpreter.set_debug_hook(Box::new(Recorder { events: events.clone(), abort: abort }));
+    (interpreter, events)
+}
+
+#[test]
+#[allow(clippy::useless_format)]
+fn hook_sees_every_list() {
+    let (mut interpreter, events) = recorder(false);
+    let result = interpreter.eval_string(format!("add 1 (len '(a b))")).unwrap();
+    assert_eq!(result, Value::Integer(3));
+    let events = events.lock().unwrap();
+    assert_eq!(events.before, vec!("add", "len", "quote"));
+    assert_eq!(events.after, vec!("quote = {a, b}", "len = 2", "add = 3"));
+    assert_eq!(events.breaks, 0);
+}
+
+#[test]
+#[allow(clippy::useless_format)]
+fn break_calls_the_hook() {
+    let (mut interpreter, events) = recorder(false);
+    let result = interpreter.eval_string(format!("seq (break) (add 1 2)")).unwrap();
+    assert_eq!(result, Value::Integer(3));
+    assert_eq!(events.lock().unwrap().breaks, 1);
+}
+
+#[test]
+#[allow(clippy::useless_format)]
+fn hook_error_aborts_evaluation() {
+    let (mut interpreter, events) = recorder(true);
+    assert!(interpreter.eval_string(format!("seq (break) (len '(a))")).is_err());
+    let events = events.lock().unwrap();
+    assert_eq!(events.breaks, 1);
+    assert!(!events.before.contains(&format!("len")));
+}
+
+#[test]
+#[allow(clippy::useless_format)]
+fn break_without_hook_does_nothing() {
+    let mut interpreter = Interpreter::new_empty();
+    assert_eq!(interpreter.eval_string(format!("seq (break) (add 1 2)")).unwrap(), Value::Integer(3));
+    assert!(interpreter.take_debug_hook().is_none());
+}
+
+//runs a script with 'ali --debug' and the commands on stdin, returns the output
+fn debug_script(script: &str, commands: &str) -> String {
+    let path = env::temp_dir().join(format!("debugger_test_{}_{}.ali", std::process::id(), script.len()));
+    fs::write(&path, script).unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ali"))
+        .arg("--debug")
+        .arg(&path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(commands.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    let _ = fs::remove_file(&path);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn debugger_stops_at_break() {
+    let output = debug_script("(set 'x 41)\n(break)\n(print (add x 1))\n", "c\np (add x 1)\nc\n");
+    assert!(output.contains("reached 'break'."), "{}", output);
+    assert!(output.contains("  42"), "{}", output);
+}
+
+#[test]
+fn debugger_stops_at_function_breakpoint() {
+    let output = debug_script("(defun 'double '(x) '(mul x 2))\n(print (double 21))\n", "b double\nc\nl\nc\n");
+    assert!(output.contains("{double, 21}"), "{}", output);
+    assert!(output.contains("42"), "{}", output);
+}
\ No newline at end of file
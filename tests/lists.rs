@@ -0,0 +1,37 @@
+extern crate alisplib;
+
+use alisplib::list::List;
+use alisplib::map::Map;
+use alisplib::value::Value;
+
+fn map(key: &str) -> Value {
+    let mut map = Map::new();
+    map.insert(Value::new_string(key), Value::Integer(1));
+    Value::Map(map)
+}
+
+//the address of the entries of a map, a clone of the map has other entries
+fn entries(value: &Value) -> *const (Value, Value) {
+    match value {
+        Value::Map(map) => map.entries().as_ptr(),
+        _ => panic!("expected a map, found {}", value)
+    }
+}
+
+#[test]
+fn into_cells_moves_the_elements_of_a_tracked_list() {
+    //the first cons copies into a chunk with free slots, the second one writes a map into it, so the collector tracks it
+    let list = List::from_cells(vec!(Value::Integer(0))).cons(map("a")).cons(map("b"));
+    let before = entries(&list.cells()[0]);
+    let cells = list.into_cells();
+    assert_eq!(entries(&cells[0]), before);
+}
+
+#[test]
+fn into_cells_clones_the_elements_of_a_shared_list() {
+    let list = List::from_cells(vec!(Value::Integer(0))).cons(map("a")).cons(map("b"));
+    let shared = list.clone();
+    let cells = list.into_cells();
+    assert!(entries(&cells[0]) != entries(&shared.cells()[0]));
+    assert_eq!(cells, shared.cells());
+}
\ No newline at end of file
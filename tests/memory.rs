@@ -0,0 +1,44 @@
+extern crate alisplib;
+
+use std::fs;
+use std::sync::Mutex;
+
+use alisplib::interpreter::Interpreter;
+
+//the tests measure the memory of the whole process, so they do not run at the same time
+static SERIAL: Mutex<()> = Mutex::new(());
+
+//how much the resident memory may grow while a loop runs a second time
+const MAX_GROWTH_KB: usize = 8 * 1024;
+
+//the resident memory in kB, none if the system does not tell it
+fn rss_kb() -> Option<usize> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|x| x.starts_with("VmRSS:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+//runs the loop body N times, then 4N times more, the memory after the first run has to stay about the same
+#[allow(clippy::single_match)]
+fn assert_bounded(body: &str, n: usize) {
+    let _serial = SERIAL.lock().unwrap_or_else(|x| x.into_inner());
+    let mut interpreter = Interpreter::new();
+    let code = |count: usize| format!("seq (set 'i 0) (while '(lt i {}) '(seq {} (set 'i (+ i 1))))", count, body);
+    interpreter.eval_string(code(n)).unwrap();
+    let before = rss_kb();
+    interpreter.eval_string(code(4 * n)).unwrap();
+    match (before, rss_kb()) {
+        (Some(before), Some(after)) => assert!(after < before + MAX_GROWTH_KB, "{}: {} kB -> {} kB", body, before, after),
+        _ => {}
+    }
+}
+
+#[test]
+fn cons_onto_itself_does_not_grow() {
+    assert_bounded("(set 'b (cons 0 (count 1 50))) (cons b b) (cons (tail b) b)", 5000);
+}
+
+#[test]
+fn closure_cycles_do_not_grow() {
+    assert_bounded("(let ((a (atom nil))) (reset! a (partial (|x y| + y 0) a)))", 20000);
+}
\ No newline at end of file
@@ -0,0 +1,49 @@
+extern crate alisplib;
+
+use std::io::Result as IOResult;
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use alisplib::interpreter::Interpreter;
+use alisplib::trace::Tracer;
+
+//a writer whose content can be read after the tracer was given to the interpreter
+#[derive(Clone, Default)]
+struct Log(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Log {
+    fn write(&mut self, buf: &[u8]) -> IOResult<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> IOResult<()> {
+        Ok(())
+    }
+}
+
+fn trace(code: &str, names: &[&str]) -> Vec<String> {
+    let log = Log::default();
+    let mut tracer = Tracer::new(Box::new(log.clone()));
+    for name in names {
+        tracer.trace(name.to_string());
+    }
+    let mut interpreter = Interpreter::new();
+    interpreter.set_tracer(tracer);
+    interpreter.eval_string(code.to_string()).unwrap();
+    let bytes = log.0.lock().unwrap().clone();
+    String::from_utf8(bytes).unwrap().lines().map(|x| x.to_string()).collect()
+}
+
+#[test]
+fn lambdas_are_logged_with_values() {
+    let lines = trace("seq (defun 'inc '(x) '(add x 1)) (inc (mul 2 3))", &["inc"]);
+    assert_eq!(lines, vec!("> (inc 6)", "< inc = 7"));
+}
+
+#[test]
+fn builtins_are_marked_as_unevaluated() {
+    let lines = trace("seq (set 'x 5) (add x (sub x 1))", &["add", "sub"]);
+    assert_eq!(lines, vec!("> (add x {sub, x, 1}) [unevaluated]", "  > (sub x 1) [unevaluated]", "  < sub = 4", "< add = 9"));
+}
\ No newline at end of file